@@ -0,0 +1,54 @@
+//! Measures the cost of creating reports with backtrace capture enabled
+//!
+//! Run with `cargo bench --bench report_creation`.
+use nocolor_eyre::eyre::{eyre, Report};
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+const ITERATIONS: u32 = 200;
+
+#[inline(never)]
+fn create_report() -> Report {
+    eyre!("error")
+}
+
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) -> Duration {
+    // Warm up caches of the symbolizer and the allocator.
+    for _ in 0..10 {
+        black_box(f());
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let per_iter = start.elapsed() / ITERATIONS;
+
+    println!("{:<40} {:>12.2?}/iter", name, per_iter);
+    per_iter
+}
+
+fn main() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    nocolor_eyre::install().unwrap();
+
+    let eager = bench(
+        "resolved backtrace::Backtrace::new",
+        backtrace::Backtrace::new,
+    );
+    bench(
+        "unresolved backtrace::Backtrace::new",
+        backtrace::Backtrace::new_unresolved,
+    );
+    let lazy = bench("create report", create_report);
+    bench("create and format report", || {
+        format!("{:?}", create_report())
+    });
+
+    println!(
+        "creating a report is {:.1}x cheaper than an eagerly resolved backtrace",
+        eager.as_secs_f64() / lazy.as_secs_f64()
+    );
+}
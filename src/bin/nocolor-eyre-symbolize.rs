@@ -0,0 +1,252 @@
+//! Symbolize nocolor-eyre reports printed with `HookBuilder::display_raw_addresses`
+//!
+//! ```text
+//! nocolor-eyre-symbolize <BINARY> [REPORT]
+//! ```
+//!
+//! `BINARY` is the unstripped binary or the separate debug file matching the
+//! binary that printed the report. The report is read from `REPORT`, or stdin
+//! if omitted, and written to stdout with every frame of `BINARY` symbolized.
+//! Frames of other modules are left untouched.
+use addr2line::object::{self, Object};
+use nocolor_eyre::eyre::{eyre, WrapErr};
+use std::{
+    borrow::Cow,
+    ffi::OsStr,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const USAGE: &str = "usage: nocolor-eyre-symbolize <BINARY> [REPORT]";
+
+fn main() -> nocolor_eyre::Result<()> {
+    nocolor_eyre::install()?;
+
+    let mut args = std::env::args_os().skip(1);
+    let binary = match args.next() {
+        Some(arg) if arg == "-h" || arg == "--help" => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Some(arg) => PathBuf::from(arg),
+        None => return Err(eyre!(USAGE)),
+    };
+
+    let report = match args.next() {
+        Some(path) => fs::read_to_string(&path)
+            .wrap_err_with(|| format!("failed to read report {}", Path::new(&path).display()))?,
+        None => {
+            let mut report = String::new();
+            io::stdin()
+                .read_to_string(&mut report)
+                .wrap_err("failed to read report from stdin")?;
+            report
+        }
+    };
+
+    let data = fs::read(&binary)
+        .wrap_err_with(|| format!("failed to read binary {}", binary.display()))?;
+    let file = object::File::parse(&*data)
+        .wrap_err_with(|| format!("failed to parse binary {}", binary.display()))?;
+    let symbolizer = Symbolizer {
+        name: binary.file_name(),
+        build_id: file.build_id().ok().flatten(),
+        context: addr2line::Context::new(&file).wrap_err("failed to load debug info")?,
+        symbols: file.symbol_map(),
+    };
+
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let lines: Vec<&str> = report.lines().collect();
+    let mut i = 0;
+
+    while i < lines.len() {
+        if frame_number_prefix(lines[i]).is_none() {
+            writeln!(out, "{}", lines[i])?;
+            i += 1;
+            continue;
+        }
+
+        // A frame spans its header and the lines indented deeper than it.
+        let indent = indentation(lines[i]);
+        let len = 1 + lines[i + 1..]
+            .iter()
+            .take_while(|line| frame_number_prefix(line).is_none() && indentation(line) > indent)
+            .count();
+        let block = &lines[i..i + len];
+
+        match symbolizer.symbolize(block) {
+            Some(symbolized) => out.write_all(symbolized.as_bytes())?,
+            None => {
+                for line in block {
+                    writeln!(out, "{}", line)?;
+                }
+            }
+        }
+
+        i += len;
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+struct Symbolizer<'data> {
+    name: Option<&'data OsStr>,
+    build_id: Option<&'data [u8]>,
+    context: addr2line::Context<addr2line::gimli::EndianRcSlice<addr2line::gimli::RunTimeEndian>>,
+    symbols: object::SymbolMap<object::SymbolMapName<'data>>,
+}
+
+impl Symbolizer<'_> {
+    /// Render the frame in `block` in the usual frame layout, if it has a raw
+    /// address line belonging to this binary
+    fn symbolize(&self, block: &[&str]) -> Option<String> {
+        let raw_line = block
+            .iter()
+            .find(|line| line.trim_start().starts_with("ip=0x"))?;
+        let raw = RawAddress::parse(raw_line.trim_start())?;
+
+        let matches = match (raw.build_id.as_deref(), self.build_id) {
+            (Some(report), Some(binary)) => hex(binary) == report,
+            _ => raw.module.map(|path| Path::new(path).file_name()) == Some(self.name),
+        };
+        if !matches {
+            return None;
+        }
+
+        // The instruction address is the return address, step back into the
+        // call instruction.
+        let address = raw.ip.checked_sub(raw.base.unwrap_or(0))?.checked_sub(1)? as u64;
+        let mut symbols = Vec::new();
+
+        if let Ok(mut frames) = self.context.find_frames(address).skip_all_loads() {
+            while let Ok(Some(frame)) = frames.next() {
+                let name = frame
+                    .function
+                    .as_ref()
+                    .and_then(|function| function.demangle().ok())
+                    .map(Cow::into_owned);
+                let location = frame.location.map(|location| {
+                    (
+                        location.file.map(str::to_owned),
+                        location.line,
+                        location.column,
+                    )
+                });
+                symbols.push((name, location));
+            }
+        }
+
+        // Without debug info fall back to the symbol table.
+        if symbols.iter().all(|(name, _)| name.is_none()) {
+            let name = self.symbols.get(address).map(|symbol| {
+                addr2line::demangle_auto(Cow::Borrowed(symbol.name()), None).into_owned()
+            });
+            symbols = vec![(name, None)];
+        }
+
+        let header = block[0];
+        let number = frame_number_prefix(header)?;
+        // Symbol and location lines are indented like the raw address line.
+        let indent = " ".repeat(indentation(raw_line).saturating_sub(4));
+        let mut out = String::new();
+
+        for (i, (name, location)) in symbols.iter().enumerate() {
+            if i == 0 {
+                out.push_str(number);
+            } else {
+                out.push_str(&indent);
+                out.push_str("    ");
+            }
+            out.push_str(name.as_deref().unwrap_or("<unknown>"));
+            if i + 1 < symbols.len() {
+                out.push_str(" [inlined]");
+            }
+            out.push('\n');
+
+            let (file, line, column) = location.clone().unwrap_or((None, None, None));
+            out.push_str(&indent);
+            out.push_str("    at ");
+            out.push_str(file.as_deref().unwrap_or("<unknown source file>"));
+            match (line, column) {
+                (Some(line), Some(column)) => out.push_str(&format!(":{}:{}", line, column)),
+                (Some(line), None) => out.push_str(&format!(":{}", line)),
+                _ => out.push_str(":<unknown line>"),
+            }
+            out.push('\n');
+        }
+
+        out.push_str(&indent);
+        out.push_str("    ");
+        out.push_str(raw.line);
+        out.push('\n');
+
+        Some(out)
+    }
+}
+
+/// A parsed raw address line, see `HookBuilder::display_raw_addresses`
+struct RawAddress<'a> {
+    line: &'a str,
+    ip: usize,
+    base: Option<usize>,
+    build_id: Option<String>,
+    module: Option<&'a str>,
+}
+
+impl<'a> RawAddress<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        // The module path comes last as it may contain spaces.
+        let (fields, module) = match line.find(" module=") {
+            Some(start) => (&line[..start], Some(&line[start + " module=".len()..])),
+            None => (line, None),
+        };
+
+        let mut raw = RawAddress {
+            line,
+            ip: 0,
+            base: None,
+            build_id: None,
+            module,
+        };
+
+        for field in fields.split(' ') {
+            let (key, value) = field.split_at(field.find('=')?);
+            let value = &value[1..];
+            match key {
+                "ip" => raw.ip = parse_address(value)?,
+                "base" => raw.base = Some(parse_address(value)?),
+                "build-id" => raw.build_id = Some(value.to_owned()),
+                _ => (),
+            }
+        }
+
+        Some(raw)
+    }
+}
+
+fn parse_address(value: &str) -> Option<usize> {
+    usize::from_str_radix(value.strip_prefix("0x")?, 16).ok()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The `  12: ` prefix of a frame header line, if `line` is one
+fn frame_number_prefix(line: &str) -> Option<&str> {
+    let digits = line.trim_start();
+    let len = digits.find(|c: char| !c.is_ascii_digit())?;
+    if len == 0 || !digits[len..].starts_with(": ") {
+        return None;
+    }
+
+    Some(&line[..line.len() - digits.len() + len + 2])
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
@@ -0,0 +1,103 @@
+//! Backtrace capture with symbol resolution deferred until formatting, and
+//! limits on how often backtraces are captured
+use backtrace::Backtrace;
+use once_cell::sync::OnceCell;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+/// A backtrace captured without resolving its symbols
+///
+/// Resolving symbols is by far the most expensive part of capturing a
+/// backtrace, and most reports are dropped without ever being printed, so it
+/// only happens the first time the resolved backtrace is requested.
+pub(crate) struct LazyBacktrace {
+    unresolved: Backtrace,
+    resolved: OnceCell<Backtrace>,
+}
+
+impl LazyBacktrace {
+    /// Capture the current backtrace without resolving its symbols
+    pub(crate) fn capture() -> Self {
+        Self {
+            unresolved: Backtrace::new_unresolved(),
+            resolved: OnceCell::new(),
+        }
+    }
+
+    /// The backtrace with all symbols resolved, resolving them if needed
+    pub(crate) fn resolved(&self) -> &Backtrace {
+        self.resolved.get_or_init(|| {
+            let mut backtrace = self.unresolved.clone();
+            backtrace.resolve();
+            backtrace
+        })
+    }
+}
+
+/// A limit on how many backtraces are captured for error reports
+///
+/// Reports created after the budget is used up have no backtrace, as if
+/// backtrace capture was disabled via `RUST_LIB_BACKTRACE`, and say so in
+/// their environment section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum CaptureBudget {
+    /// Capture a backtrace for every report
+    #[default]
+    Unlimited,
+    /// Capture at most this many backtraces per second
+    PerSecond(u32),
+    /// Capture a backtrace for one in every this many reports
+    Sample(u32),
+}
+
+/// Tracks how much of a `CaptureBudget` has been used
+pub(crate) struct CaptureLimiter {
+    budget: CaptureBudget,
+    /// The start of the current one second window and the captures in it,
+    /// created on first use as `Instant::now` panics on some targets
+    window: Mutex<Option<(Instant, u32)>>,
+    count: AtomicU64,
+}
+
+impl CaptureLimiter {
+    pub(crate) fn new(budget: CaptureBudget) -> Self {
+        Self {
+            budget,
+            window: Mutex::new(None),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    /// Take one capture from the budget, returning whether a backtrace may be
+    /// captured
+    pub(crate) fn try_acquire(&self) -> bool {
+        match self.budget {
+            CaptureBudget::Unlimited => true,
+            CaptureBudget::PerSecond(max) => {
+                let mut window = self.window.lock().unwrap_or_else(PoisonError::into_inner);
+                let (start, count) = window.get_or_insert_with(|| (Instant::now(), 0));
+
+                if start.elapsed() >= Duration::from_secs(1) {
+                    *start = Instant::now();
+                    *count = 0;
+                }
+
+                if *count < max {
+                    *count += 1;
+                    true
+                } else {
+                    false
+                }
+            }
+            CaptureBudget::Sample(n) => {
+                self.count.fetch_add(1, Ordering::Relaxed) % u64::from(n.max(1)) == 0
+            }
+        }
+    }
+}
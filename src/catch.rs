@@ -0,0 +1,161 @@
+//! Conversion of caught panics into error reports
+use crate::{capture::LazyBacktrace, fmt::ThreadSection};
+use eyre::Report;
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    panic::UnwindSafe,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+thread_local! {
+    static CATCHING: Cell<usize> = const { Cell::new(0) };
+    static CAUGHT: RefCell<Vec<CaughtPanic>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The details of a panic, captured by the panic hook on the panicking thread
+///
+/// Nothing is rendered until the report created from it is displayed.
+pub(crate) struct CaughtPanic {
+    pub(crate) message: String,
+    pub(crate) location: Option<PanicLocation>,
+    pub(crate) thread: ThreadSection,
+    pub(crate) backtrace: Option<LazyBacktrace>,
+    #[cfg(feature = "tracing-error")]
+    pub(crate) span_trace: Option<tracing_error::SpanTrace>,
+    /// The `HookBuilder::panic_section`
+    pub(crate) section: Option<Arc<dyn fmt::Display + Send + Sync + 'static>>,
+}
+
+/// The source location of a caught panic
+#[derive(Debug)]
+pub(crate) struct PanicLocation {
+    pub(crate) file: String,
+    pub(crate) line: u32,
+    pub(crate) column: u32,
+}
+
+impl From<&std::panic::Location<'_>> for PanicLocation {
+    fn from(location: &std::panic::Location<'_>) -> Self {
+        Self {
+            file: location.file().to_owned(),
+            line: location.line(),
+            column: location.column(),
+        }
+    }
+}
+
+/// Whether the current thread is running a closure passed to `catch_panic`
+pub(crate) fn catching() -> bool {
+    CATCHING.try_with(Cell::get).unwrap_or(0) > 0
+}
+
+/// Hand the details of a panic to the enclosing `catch_panic` call
+pub(crate) fn store(caught: CaughtPanic) {
+    let _ = CAUGHT.try_with(|caught_panics| caught_panics.borrow_mut().push(caught));
+}
+
+/// The error of a `Report` created from a panic by [`catch_panic`]
+///
+/// Displays as the panic message. The rest of the panic, like its location,
+/// backtrace and thread, is carried by the report's [`Handler`].
+///
+/// [`Handler`]: crate::Handler
+pub struct PanicError {
+    message: String,
+    /// Handed to the eyre hook, which creates the report's `Handler` from it
+    caught: Mutex<Option<CaughtPanic>>,
+}
+
+impl PanicError {
+    /// The panic message, rendered like in panic reports
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The details captured by the panic hook, unless already taken
+    pub(crate) fn take_caught(&self) -> Option<CaughtPanic> {
+        self.caught
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+}
+
+impl fmt::Debug for PanicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PanicError")
+            .field("message", &self.message)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for PanicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for PanicError {}
+
+/// Runs `f`, converting a panic into a `Report`
+///
+/// # Details
+///
+/// When the panic hook of this crate is installed it captures the payload,
+/// location, backtrace and `SpanTrace` of the panic on the panicking thread,
+/// and the returned report renders them with the same sections as a panic
+/// report. The panic hook still reports the panic, like any other panic
+/// caught with `catch_unwind`. Without the panic hook the report is created
+/// from the payload like any other report.
+///
+/// # Examples
+///
+/// ```rust
+/// nocolor_eyre::install().unwrap();
+///
+/// let report = nocolor_eyre::catch_panic(|| panic!("job failed")).unwrap_err();
+/// assert_eq!(report.to_string(), "job failed");
+/// ```
+pub fn catch_panic<F, R>(f: F) -> Result<R, Report>
+where
+    F: FnOnce() -> R + UnwindSafe,
+{
+    let start = CAUGHT.with(|caught_panics| {
+        let mut caught_panics = caught_panics.borrow_mut();
+        // Never attribute panics stored outside of this call to it.
+        if !catching() {
+            caught_panics.clear();
+        }
+        caught_panics.len()
+    });
+
+    CATCHING.with(|catching| catching.set(catching.get() + 1));
+    let result = std::panic::catch_unwind(f);
+    CATCHING.with(|catching| catching.set(catching.get() - 1));
+
+    // Only the last panic can have unwound out of `f`, the earlier ones were
+    // caught within it.
+    let caught = CAUGHT.with(|caught_panics| {
+        let mut caught_panics = caught_panics.borrow_mut();
+        let start = start.min(caught_panics.len());
+        caught_panics.split_off(start).pop()
+    });
+
+    let payload = match result {
+        Ok(value) => return Ok(value),
+        Err(payload) => payload,
+    };
+
+    let message = match caught.as_ref() {
+        Some(caught) => caught.message.clone(),
+        None => crate::payload::PayloadFormatters::default()
+            .message(&*payload)
+            .into_owned(),
+    };
+
+    Err(Report::new(PanicError {
+        message,
+        caught: Mutex::new(caught),
+    }))
+}
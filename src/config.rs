@@ -1,49 +1,77 @@
 //! Configuration options for customizing the behavior of the provided panic
 //! and error reporting hooks
+pub use crate::capture::CaptureBudget;
+pub use crate::filter::FrameRule;
+pub use crate::sources::EmbeddedSources;
 use crate::{
-    section::PanicMessage,
-    writers::{EnvSection, WriterExt},
+    capture::{CaptureLimiter, LazyBacktrace},
+    crash::CrashReports,
+    exit::ExitCodes,
+    fmt::ThreadSection,
+    modules::{Modules, RawAddress},
+    paths::PathRewriter,
+    payload::PayloadFormatters,
+    section::{help::HelpInfo, PanicMessage},
+    writers::{EnvSection, Guarded, WriterExt},
 };
 use fmt::Display;
 use indenter::{indented, Format};
 use std::env;
 use std::fmt::Write as _;
-use std::{fmt, path::PathBuf, sync::Arc};
-
-#[derive(Debug)]
-struct InstallError;
-
-impl fmt::Display for InstallError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("could not install the BacktracePrinter as another was already installed")
-    }
-}
-
-impl std::error::Error for InstallError {}
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
 
 /// A representation of a Frame from a Backtrace or a SpanTrace
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[non_exhaustive]
 pub struct Frame {
-    /// Frame index
+    /// Symbol index, counting every symbol including inlined ones, starting at 1
     pub n: usize,
+    /// Index of the physical stack frame this symbol belongs to, starting at 0
+    pub index: usize,
+    /// Whether this symbol was inlined into the next symbol of the same
+    /// physical frame
+    pub inlined: bool,
     /// frame symbol name
     pub name: Option<String>,
     /// source line number
     pub lineno: Option<u32>,
+    /// source column number
+    pub colno: Option<u32>,
     /// source file path
     pub filename: Option<PathBuf>,
+    /// instruction address of the physical frame
+    pub ip: Option<usize>,
+    /// base address of the module containing the instruction address
+    pub module_base: Option<usize>,
 }
 
 #[derive(Debug)]
-struct StyledFrame<'a>(&'a Frame);
+struct StyledFrame<'a> {
+    frame: &'a Frame,
+    /// Whether a symbol of the same physical frame was printed right before
+    continued: bool,
+    /// The raw address of the physical frame, printed after its last symbol
+    raw_address: Option<RawAddress<'a>>,
+    charset: Charset,
+    paths: &'a PathRewriter,
+}
 
 impl<'a> fmt::Display for StyledFrame<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let Self(frame) = self;
+        let frame = self.frame;
 
-        // Print frame index.
-        write!(f, "{:>2}: ", frame.n)?;
+        // Print frame index, once per physical frame.
+        if self.continued {
+            write!(f, "    ")?;
+        } else {
+            write!(f, "{:>2}: ", frame.index)?;
+        }
 
         // Does the function have a hash suffix?
         // (dodging a dep on the regex crate here)
@@ -70,10 +98,14 @@ impl<'a> fmt::Display for StyledFrame<'a> {
         write!(f, "{}", name)?;
         write!(f, "{}", hash_suffix)?;
 
+        if frame.inlined {
+            write!(f, " [inlined]")?;
+        }
+
         let mut separated = f.header("\n");
 
         // Print source location, if known.
-        let file = frame.filename.as_ref().map(|path| path.display());
+        let file = frame.filename.as_ref().map(|path| self.paths.rewrite(path));
         let file: &dyn fmt::Display = if let Some(ref filename) = file {
             filename
         } else {
@@ -82,7 +114,20 @@ impl<'a> fmt::Display for StyledFrame<'a> {
         let lineno = frame
             .lineno
             .map_or("<unknown line>".to_owned(), |x| x.to_string());
-        write!(&mut separated.ready(), "    at {}:{}", file, lineno, )?;
+        match frame.colno {
+            Some(colno) => write!(
+                &mut separated.ready(),
+                "    at {}:{}:{}",
+                file,
+                lineno,
+                colno
+            )?,
+            None => write!(&mut separated.ready(), "    at {}:{}", file, lineno)?,
+        }
+
+        if let Some(raw_address) = self.raw_address.as_ref() {
+            write!(&mut separated.ready(), "    {}", raw_address)?;
+        }
 
         let v = if std::thread::panicking() {
             panic_verbosity()
@@ -92,46 +137,83 @@ impl<'a> fmt::Display for StyledFrame<'a> {
 
         // Maybe print source.
         if v >= Verbosity::Full {
-            write!(&mut separated.ready(), "{}", SourceSection(frame))?;
+            write!(
+                &mut separated.ready(),
+                "{}",
+                SourceSection(frame, self.paths, self.charset)
+            )?;
         }
 
         Ok(())
     }
 }
 
-struct SourceSection<'a>(&'a Frame);
+pub(crate) struct SourceSection<'a>(
+    pub(crate) &'a Frame,
+    pub(crate) &'a PathRewriter,
+    pub(crate) Charset,
+);
 
 impl fmt::Display for SourceSection<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let Self(frame) = self;
+        let Self(frame, paths, charset) = self;
 
-        let (lineno, filename) = match (frame.lineno, frame.filename.as_ref()) {
+        // Line numbers start at 1, debug info uses 0 for unknown lines.
+        let (lineno, filename) = match (frame.lineno.filter(|&n| n > 0), frame.filename.as_ref()) {
             (Some(a), Some(b)) => (a, b),
             // Without a line number and file name, we can't sensibly proceed.
             _ => return Ok(()),
         };
 
-        let file = match std::fs::File::open(filename) {
-            Ok(file) => file,
-            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
-            e @ Err(_) => e.unwrap(),
-        };
-
         use std::fmt::Write;
         use std::io::BufRead;
 
         // Extract relevant lines.
-        let reader = std::io::BufReader::new(file);
         let start_line = lineno - 2.min(lineno - 1);
-        let surrounding_src = reader.lines().skip(start_line as usize - 1).take(5);
+        let surrounding_src: Vec<(Cow<'_, str>, u32)> = match paths.find_source(filename) {
+            Some(filename) => {
+                // Unreadable files just don't get a snippet, this may run
+                // inside the panic hook where panicking aborts the process.
+                let file = match std::fs::File::open(filename) {
+                    Ok(file) => file,
+                    Err(_) => return Ok(()),
+                };
+                let reader = std::io::BufReader::new(file);
+                reader
+                    .split(b'\n')
+                    .skip(start_line as usize - 1)
+                    .take(5)
+                    .map_while(Result::ok)
+                    .map(|line| {
+                        let line = line.strip_suffix(b"\r").unwrap_or(&line);
+                        // Source files aren't necessarily valid UTF-8.
+                        Cow::Owned(String::from_utf8_lossy(line).into_owned())
+                    })
+                    .zip(start_line..)
+                    .collect()
+            }
+            // Fall back to sources compiled into the binary.
+            None => paths
+                .embedded_sources
+                .lines(
+                    filename,
+                    paths.workspace_root.as_deref(),
+                    lineno,
+                    start_line,
+                    start_line + 5,
+                )
+                .into_iter()
+                .map(|(cur_line_no, line)| (Cow::Borrowed(line), cur_line_no))
+                .collect(),
+        };
+
         let mut separated = f.header("\n");
         let mut f = separated.in_progress();
-        for (line, cur_line_no) in surrounding_src.zip(start_line..) {
-            let line = line.unwrap();
+        for (line, cur_line_no) in surrounding_src {
             if cur_line_no == lineno {
                 write!(&mut f, "{:>8} > {}", cur_line_no, line, )?;
             } else {
-                write!(&mut f, "{:>8} │ {}", cur_line_no, line)?;
+                write!(&mut f, "{:>8} {} {}", cur_line_no, charset.gutter(), line)?;
             }
             f = separated.ready();
         }
@@ -196,14 +278,68 @@ impl Frame {
     }
 }
 
+/// The layout used when rendering a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum ReportFormat {
+    /// The human readable text layout
+    #[default]
+    Text,
+    /// A single line JSON object, for consumption by log pipelines and other tools
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    Json,
+    /// Markdown with a heading per section and fenced code blocks for
+    /// backtraces, for pasting into tickets and chat
+    Markdown,
+    /// A standalone HTML document with collapsible backtraces and source
+    /// snippets, which needs no external assets or JavaScript
+    Html,
+}
+
+impl ReportFormat {
+    /// The file extension of reports in this format
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Text => "txt",
+            #[cfg(feature = "json")]
+            ReportFormat::Json => "json",
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
 /// Builder for customizing the behavior of the global panic and error report hooks
 pub struct HookBuilder {
     filters: Vec<Box<FilterCallback>>,
+    collapse_recursion: bool,
+    display_raw_addresses: bool,
+    ascii: bool,
+    capture_budget: CaptureBudget,
+    shorten_paths: bool,
+    path_mappings: Vec<(PathBuf, String)>,
+    workspace_root: Option<PathBuf>,
+    source_remaps: Vec<(PathBuf, PathBuf)>,
+    source_roots: Vec<PathBuf>,
+    embedded_sources: EmbeddedSources,
+    error_format: ReportFormat,
+    panic_format: ReportFormat,
     display_env_section: bool,
     #[cfg(feature = "track-caller")]
     display_location_section: bool,
+    /// Whether to display the thread, if set explicitly
+    display_thread_section: Option<bool>,
+    #[cfg(feature = "tracing-error")]
+    capture_span_trace_by_default: bool,
     panic_section: Option<Box<dyn Display + Send + Sync + 'static>>,
     panic_message: Option<Box<dyn PanicMessage>>,
+    payload_formatters: PayloadFormatters,
+    exit_codes: ExitCodes,
+    panic_sinks: Vec<PanicSink>,
+    crash_report_dir: Option<PathBuf>,
+    crash_report_format: Option<ReportFormat>,
+    max_crash_reports: usize,
     #[cfg(feature = "issue-url")]
     issue_url: Option<String>,
     #[cfg(feature = "issue-url")]
@@ -217,8 +353,9 @@ impl HookBuilder {
     ///
     /// # Details
     ///
-    /// By default this function calls `add_default_filters()`. To get a `HookBuilder` with all
-    /// features disabled by default call `HookBuilder::blank()`.
+    /// By default this function calls `add_default_filters()` and enables
+    /// `collapse_recursion` and `shorten_paths`. To get a `HookBuilder` with all features
+    /// disabled by default call `HookBuilder::blank()`.
     ///
     /// # Example
     ///
@@ -230,18 +367,42 @@ impl HookBuilder {
     ///     .unwrap();
     /// ```
     pub fn new() -> Self {
-        Self::blank().add_default_filters()
+        Self::blank()
+            .add_default_filters()
+            .collapse_recursion(true)
+            .shorten_paths(true)
     }
 
     /// Construct a HookBuilder with minimal features enabled
     pub fn blank() -> Self {
         HookBuilder {
             filters: vec![],
+            collapse_recursion: false,
+            display_raw_addresses: false,
+            ascii: false,
+            capture_budget: CaptureBudget::Unlimited,
+            shorten_paths: false,
+            path_mappings: vec![],
+            workspace_root: None,
+            source_remaps: vec![],
+            source_roots: vec![],
+            embedded_sources: EmbeddedSources::default(),
+            error_format: ReportFormat::Text,
+            panic_format: ReportFormat::Text,
             display_env_section: true,
             #[cfg(feature = "track-caller")]
             display_location_section: true,
+            display_thread_section: None,
+            #[cfg(feature = "tracing-error")]
+            capture_span_trace_by_default: true,
             panic_section: None,
             panic_message: None,
+            payload_formatters: PayloadFormatters::default(),
+            exit_codes: ExitCodes::default(),
+            panic_sinks: vec![],
+            crash_report_dir: None,
+            crash_report_format: None,
+            max_crash_reports: 10,
             #[cfg(feature = "issue-url")]
             issue_url: None,
             #[cfg(feature = "issue-url")]
@@ -323,6 +484,149 @@ impl HookBuilder {
         self
     }
 
+    /// Registers how to render panic payloads of type `T`
+    ///
+    /// # Details
+    ///
+    /// Panics raised via `std::panic::panic_any` with payloads other than
+    /// `String` or `&str` are shown as `<non string panic payload>`, unless a
+    /// formatter is registered for the payload's type. Registered formatters
+    /// are used for the default panic message, JSON panic reports, issue urls
+    /// and `PanicReport::payload_message`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// struct ExitCode(i32);
+    ///
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .panic_payload_formatter(|code: &ExitCode, f| write!(f, "exit code {}", code.0))
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn panic_payload_formatter<T, F>(mut self, format: F) -> Self
+    where
+        T: std::any::Any,
+        F: Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result + Send + Sync + 'static,
+    {
+        self.payload_formatters.register(format);
+        self
+    }
+
+    /// Add a writer that every rendered panic report is written to
+    ///
+    /// # Details
+    ///
+    /// Panic reports are printed to stderr until the first writer or callback
+    /// is added. After that they are only delivered to the configured sinks, so
+    /// add `std::io::stderr()` explicitly to keep printing them there as well.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let log = std::fs::File::create("panics.log").unwrap();
+    ///
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .add_panic_writer(std::io::stderr())
+    ///     .add_panic_writer(log)
+    ///     .install()
+    ///     .unwrap();
+    /// # std::fs::remove_file("panics.log").unwrap();
+    /// ```
+    pub fn add_panic_writer<W: io::Write + Send + 'static>(mut self, writer: W) -> Self {
+        self.panic_sinks
+            .push(PanicSink::Writer(Mutex::new(Box::new(writer))));
+        self
+    }
+
+    /// Add a callback that receives every panic report
+    ///
+    /// # Details
+    ///
+    /// Like writers added via `add_panic_writer`, adding a callback stops the
+    /// default printing of panic reports to stderr.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .add_panic_callback(|report| {
+    ///         let report = report.to_string();
+    ///         // forward the report to a supervisor process ...
+    ///     })
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn add_panic_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&PanicReport<'_>) + Send + Sync + 'static,
+    {
+        self.panic_sinks
+            .push(PanicSink::Callback(Box::new(callback)));
+        self
+    }
+
+    /// Also write every panic report to a timestamped file in `dir`
+    ///
+    /// # Details
+    ///
+    /// The directory is created if it doesn't exist yet. After a report has been
+    /// written a short note pointing at the file is printed to stderr, and all
+    /// but the newest `max_crash_reports` files are removed from the directory.
+    /// [`default_crash_report_dir`] returns a platform appropriate location for
+    /// the given application name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nocolor_eyre::config::{default_crash_report_dir, HookBuilder};
+    ///
+    /// let mut builder = HookBuilder::default();
+    /// if let Some(dir) = default_crash_report_dir(env!("CARGO_PKG_NAME")) {
+    ///     builder = builder.crash_report_dir(dir);
+    /// }
+    /// builder.install().unwrap();
+    /// ```
+    pub fn crash_report_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.crash_report_dir = Some(dir.into());
+        self
+    }
+
+    /// Configures the layout of crash report files, defaults to the format set
+    /// via `panic_format`
+    ///
+    /// The file extension of the reports follows the format, for example
+    /// `.html` for `ReportFormat::Html`.
+    ///
+    /// **Note**: this setting will be ignored if no `crash_report_dir` is set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nocolor_eyre::config::{HookBuilder, ReportFormat};
+    ///
+    /// HookBuilder::default()
+    ///     .crash_report_dir(std::env::temp_dir().join("my-app-crashes"))
+    ///     .crash_report_format(ReportFormat::Html)
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn crash_report_format(mut self, format: ReportFormat) -> Self {
+        self.crash_report_format = Some(format);
+        self
+    }
+
+    /// Configures how many crash report files are kept in the crash report
+    /// directory, defaults to 10
+    ///
+    /// The newest report is always kept, even if `max` is zero.
+    ///
+    /// **Note**: this setting will be ignored if no `crash_report_dir` is set.
+    pub fn max_crash_reports(mut self, max: usize) -> Self {
+        self.max_crash_reports = max;
+        self
+    }
+
     /// Set an upstream github repo and enable issue reporting url generation
     ///
     /// # Details
@@ -384,14 +688,8 @@ impl HookBuilder {
     /// nocolor_eyre::config::HookBuilder::default()
     ///     .issue_url(concat!(env!("CARGO_PKG_REPOSITORY"), "/issues/new"))
     ///     .issue_filter(|kind| match kind {
-    ///         nocolor_eyre::ErrorKind::NonRecoverable(payload) => {
-    ///             let payload = payload
-    ///                 .downcast_ref::<String>()
-    ///                 .map(String::as_str)
-    ///                 .or_else(|| payload.downcast_ref::<&str>().cloned())
-    ///                 .unwrap_or("<non string panic payload>");
-    ///
-    ///             !payload.contains("my irrelevant error message")
+    ///         nocolor_eyre::ErrorKind::NonRecoverable(_, message) => {
+    ///             !message.contains("my irrelevant error message")
     ///         },
     ///         nocolor_eyre::ErrorKind::Recoverable(error) => !error.is::<std::fmt::Error>(),
     ///     })
@@ -408,107 +706,659 @@ impl HookBuilder {
         self
     }
 
-    /// Configures the enviroment varible info section and whether or not it is displayed
-    pub fn display_env_section(mut self, cond: bool) -> Self {
-        self.display_env_section = cond;
+    /// Configures the layout used when rendering `eyre::Report`s via `Debug`
+    ///
+    /// # Details
+    ///
+    /// The JSON layout contains the same information as the text layout, the
+    /// chain of errors, the location, all attached sections, the filtered
+    /// backtrace frames and the environment variable hints, as a single JSON
+    /// object. The alternate `{:#?}` format is unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "json")]
+    /// # {
+    /// use nocolor_eyre::config::{HookBuilder, ReportFormat};
+    ///
+    /// HookBuilder::default()
+    ///     .error_format(ReportFormat::Json)
+    ///     .install()
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn error_format(mut self, format: ReportFormat) -> Self {
+        self.error_format = format;
         self
     }
 
-    /// Configures the location info section and whether or not it is displayed.
+    /// Configures the layout used when printing panic reports
     ///
-    /// # Notes
+    /// # Details
     ///
-    /// This will not disable the location section in a panic message.
-    #[cfg(feature = "track-caller")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "track-caller")))]
-    pub fn display_location_section(mut self, cond: bool) -> Self {
-        self.display_location_section = cond;
+    /// The JSON layout prints each panic as a single line JSON object containing
+    /// the panic payload, the location, the name of the panicking thread, the
+    /// filtered backtrace frames, the custom panic section and the issue url, if
+    /// one is configured.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "json")]
+    /// # {
+    /// use nocolor_eyre::config::{HookBuilder, ReportFormat};
+    ///
+    /// HookBuilder::default()
+    ///     .panic_format(ReportFormat::Json)
+    ///     .install()
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn panic_format(mut self, format: ReportFormat) -> Self {
+        self.panic_format = format;
         self
     }
 
-    /// Add a custom filter to the set of frame filters
+    /// Configures whether repeated cycles of frames, such as those produced by
+    /// deep recursion, are collapsed into a single copy in backtraces
+    ///
+    /// # Details
+    ///
+    /// A cycle of up to 64 consecutive frames that occurs at least three times
+    /// in a row is printed once, followed by a marker like
+    /// `⋮ previous 3 frames repeated 120 times ⋮`. Enabled by
+    /// `HookBuilder::new`, but not by `HookBuilder::blank`. Like frame
+    /// filtering, collapsing is disabled by `COLORBT_SHOW_HIDDEN=1`.
     ///
     /// # Examples
     ///
     /// ```rust
     /// nocolor_eyre::config::HookBuilder::default()
-    ///     .add_frame_filter(Box::new(|frames| {
-    ///         let filters = &[
-    ///             "uninteresting_function",
-    ///         ];
+    ///     .collapse_recursion(false)
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn collapse_recursion(mut self, cond: bool) -> Self {
+        self.collapse_recursion = cond;
+        self
+    }
+
+    /// Configures whether backtraces include the raw instruction address of
+    /// every frame, for symbolizing reports from stripped binaries offline
     ///
-    ///         frames.retain(|frame| {
-    ///             !filters.iter().any(|f| {
-    ///                 let name = if let Some(name) = frame.name.as_ref() {
-    ///                     name.as_str()
-    ///                 } else {
-    ///                     return true;
-    ///                 };
+    /// # Details
     ///
-    ///                 name.starts_with(f)
-    ///             })
-    ///         });
-    ///     }))
+    /// Each physical frame gets an extra line like
+    /// `ip=0x55d4c3a1b2c3 base=0x55d4c3a00000 build-id=3f2a... module=/usr/bin/app`
+    /// with the instruction address, the load base, the ELF build-id and the
+    /// path of the module containing it. Frames without a symbol name are
+    /// exempt from frame filtering in this mode. Module information is only
+    /// available on Linux.
+    ///
+    /// Reports in this format can be symbolized with the unstripped binary or
+    /// its debug file by the `nocolor-eyre-symbolize` binary, which is built
+    /// with the `symbolize` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .display_raw_addresses(true)
     ///     .install()
     ///     .unwrap();
     /// ```
-    pub fn add_frame_filter(mut self, filter: Box<FilterCallback>) -> Self {
-        self.filters.push(filter);
+    pub fn display_raw_addresses(mut self, cond: bool) -> Self {
+        self.display_raw_addresses = cond;
         self
     }
 
-    /// Install the given Hook as the global error report hook
-    pub fn install(self) -> Result<(), crate::eyre::Report> {
-        let (panic_hook, eyre_hook) = self.try_into_hooks()?;
-        eyre_hook.install()?;
-        panic_hook.install();
-        Ok(())
+    /// Configures whether backtraces and source snippets are decorated with
+    /// plain ASCII characters only, for consoles and log collectors that
+    /// mangle Unicode
+    ///
+    /// # Details
+    ///
+    /// The `│` between line numbers and source lines becomes `|`, and the
+    /// `⋮` markers for hidden and repeated frames are replaced with labeled
+    /// lines like `hidden frames: 3`, which also read better with screen
+    /// readers.
+    ///
+    /// Setting the `NOCOLOR_EYRE_ASCII` environment variable to `1`, `on` or
+    /// `y` enables this mode at runtime, and setting it to `0`, `off` or `n`
+    /// disables it, regardless of this setting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .ascii_output(true)
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn ascii_output(mut self, cond: bool) -> Self {
+        self.ascii = cond;
+        self
     }
 
-    /// Add the default set of filters to this `HookBuilder`'s configuration
-    pub fn add_default_filters(self) -> Self {
-        self.add_frame_filter(Box::new(default_frame_filter))
-            .add_frame_filter(Box::new(eyre_frame_filters))
+    /// Limits how many backtraces are captured for error reports
+    ///
+    /// # Details
+    ///
+    /// Capturing a backtrace for every report can use a lot of CPU time when
+    /// many errors are created in a short time. Reports created after the
+    /// budget is used up have no backtrace, and their environment section
+    /// notes that the backtrace was skipped by the rate limiter. Panic reports
+    /// are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nocolor_eyre::config::{CaptureBudget, HookBuilder};
+    ///
+    /// HookBuilder::default()
+    ///     .capture_budget(CaptureBudget::PerSecond(10))
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn capture_budget(mut self, budget: CaptureBudget) -> Self {
+        self.capture_budget = budget;
+        self
     }
 
-    /// Create a `PanicHook` and `EyreHook` from this `HookBuilder`.
-    /// This can be used if you want to combine these handlers with other handlers.
-    pub fn into_hooks(self) -> (PanicHook, EyreHook) {
-        self.try_into_hooks().expect("into_hooks should only be called when no `color_spantrace` themes have previously been set")
+    /// Configures whether paths into the Cargo registry and the standard
+    /// library sources are shortened in frames and locations
+    ///
+    /// # Details
+    ///
+    /// When enabled,
+    /// `/home/ci/.cargo/registry/src/index.crates.io-6f17d22bba15001f/tokio-1.2.3/src/lib.rs`
+    /// is printed as `[tokio-1.2.3]/src/lib.rs` and
+    /// `/rustc/<commit hash>/library/core/src/ops/function.rs` as
+    /// `[std]/core/src/ops/function.rs`. Enabled by `HookBuilder::new`, but
+    /// not by `HookBuilder::blank`.
+    pub fn shorten_paths(mut self, cond: bool) -> Self {
+        self.shorten_paths = cond;
+        self
     }
 
-    /// Create a `PanicHook` and `EyreHook` from this `HookBuilder`.
-    /// This can be used if you want to combine these handlers with other handlers.
-    pub fn try_into_hooks(self) -> Result<(PanicHook, EyreHook), crate::eyre::Report> {
-        #[cfg(feature = "issue-url")]
-            let metadata = Arc::new(self.issue_metadata);
-        let panic_hook = PanicHook {
-            filters: self.filters.into(),
-            section: self.panic_section,
-            display_env_section: self.display_env_section,
-            panic_message: self
-                .panic_message
-                .unwrap_or_else(|| Box::new(DefaultPanicMessage)),
-            #[cfg(feature = "issue-url")]
-            issue_url: self.issue_url.clone(),
-            #[cfg(feature = "issue-url")]
-            issue_metadata: metadata.clone(),
-            #[cfg(feature = "issue-url")]
-            issue_filter: self.issue_filter.clone(),
-        };
-
-        let eyre_hook = EyreHook {
-            filters: panic_hook.filters.clone(),
-            display_env_section: self.display_env_section,
-            #[cfg(feature = "track-caller")]
-            display_location_section: self.display_location_section,
-            #[cfg(feature = "issue-url")]
-            issue_url: self.issue_url,
-            #[cfg(feature = "issue-url")]
-            issue_metadata: metadata,
-            #[cfg(feature = "issue-url")]
-            issue_filter: self.issue_filter,
-        };
+    /// Print paths starting with `prefix` in frames and locations with
+    /// `prefix` replaced by `replacement`
+    ///
+    /// # Details
+    ///
+    /// Mappings are tried in the order they were added and take precedence
+    /// over the workspace root and path shortening.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .add_path_mapping("/home/ci/build/vendor", "[vendor]")
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn add_path_mapping<P, S>(mut self, prefix: P, replacement: S) -> Self
+    where
+        P: Into<PathBuf>,
+        S: Into<String>,
+    {
+        self.path_mappings.push((prefix.into(), replacement.into()));
+        self
+    }
+
+    /// Print paths inside `root` in frames and locations relative to it
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .workspace_root(env!("CARGO_MANIFEST_DIR"))
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn workspace_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.workspace_root = Some(root.into());
+        self
+    }
+
+    /// Read source snippets for files below `from` on the build machine from
+    /// below `to` instead
+    ///
+    /// # Details
+    ///
+    /// Source snippets are shown with `RUST_BACKTRACE=full`. This is useful
+    /// when the sources live somewhere else on the machine the binary runs on,
+    /// or when the binary was built with `--remap-path-prefix`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .add_source_remap("/home/ci/build", "/src")
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn add_source_remap<P, Q>(mut self, from: P, to: Q) -> Self
+    where
+        P: Into<PathBuf>,
+        Q: Into<PathBuf>,
+    {
+        self.source_remaps.push((from.into(), to.into()));
+        self
+    }
+
+    /// Search for source files that can't be found at their original path
+    /// below `root`
+    ///
+    /// # Details
+    ///
+    /// Every suffix of the original path down to its parent directory and
+    /// file name is tried below `root`, longest first, so
+    /// `/home/ci/build/app/src/main.rs` is found as `/src/app/src/main.rs` or
+    /// `/src/src/main.rs` with a root of `/src`, but not as `/src/main.rs`.
+    /// Remaps added with `add_source_remap` are tried before any root. As a
+    /// last resort, sources of crates from the Cargo registry are looked up in
+    /// the registry under `$CARGO_HOME`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .add_source_root("/src")
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn add_source_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.source_roots.push(root.into());
+        self
+    }
+
+    /// Source code compiled into the binary, for source snippets of files
+    /// that can't be found on disk
+    ///
+    /// # Details
+    ///
+    /// Embedded sources are only used once the original path, the remaps
+    /// added with `add_source_remap` and the roots added with
+    /// `add_source_root` are exhausted. See [`embed_sources!`] for embedding
+    /// files of the current crate.
+    ///
+    /// [`embed_sources!`]: crate::embed_sources
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .embedded_sources(nocolor_eyre::embed_sources!("src/lib.rs"))
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn embedded_sources(mut self, sources: EmbeddedSources) -> Self {
+        self.embedded_sources = sources;
+        self
+    }
+
+    /// Configures the enviroment varible info section and whether or not it is displayed
+    pub fn display_env_section(mut self, cond: bool) -> Self {
+        self.display_env_section = cond;
+        self
+    }
+
+    /// Configures the location info section and whether or not it is displayed.
+    ///
+    /// # Notes
+    ///
+    /// This will not disable the location in the default text panic message,
+    /// only the location section of JSON, Markdown and HTML panic reports.
+    #[cfg(feature = "track-caller")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "track-caller")))]
+    pub fn display_location_section(mut self, cond: bool) -> Self {
+        self.display_location_section = cond;
+        self
+    }
+
+    /// Configures whether panic and error reports include a section with the
+    /// name and ID of the thread that panicked or created the report
+    ///
+    /// # Notes
+    ///
+    /// Unless configured otherwise, panic reports include the thread and error
+    /// reports don't.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .display_thread_section(true)
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn display_thread_section(mut self, cond: bool) -> Self {
+        self.display_thread_section = Some(cond);
+        self
+    }
+
+    /// Configures whether `SpanTrace`s are captured for reports and panics when
+    /// the `RUST_SPANTRACE` environment variable is not set
+    ///
+    /// # Details
+    ///
+    /// Setting `RUST_SPANTRACE=0` always disables capture and setting it to any
+    /// other value always enables it, regardless of this setting. Span traces are
+    /// only populated if a `tracing_error::ErrorLayer` is part of the installed
+    /// `tracing` subscriber.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "tracing-error")]
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .capture_span_trace_by_default(false)
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "tracing-error")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tracing-error")))]
+    pub fn capture_span_trace_by_default(mut self, cond: bool) -> Self {
+        self.capture_span_trace_by_default = cond;
+        self
+    }
+
+    /// Add a custom filter to the set of frame filters
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .add_frame_filter(Box::new(|frames| {
+    ///         let filters = &[
+    ///             "uninteresting_function",
+    ///         ];
+    ///
+    ///         frames.retain(|frame| {
+    ///             !filters.iter().any(|f| {
+    ///                 let name = if let Some(name) = frame.name.as_ref() {
+    ///                     name.as_str()
+    ///                 } else {
+    ///                     return true;
+    ///                 };
+    ///
+    ///                 name.starts_with(f)
+    ///             })
+    ///         });
+    ///     }))
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn add_frame_filter(mut self, filter: Box<FilterCallback>) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Add a declarative rule to the set of frame filters
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nocolor_eyre::config::{FrameRule, HookBuilder};
+    ///
+    /// HookBuilder::default()
+    ///     .add_frame_rule(FrameRule::HideBetween {
+    ///         start: "my_app::scheduler::run".into(),
+    ///         end: "my_app::scheduler::poll".into(),
+    ///     })
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn add_frame_rule(self, rule: FrameRule) -> Self {
+        self.add_frame_filter(Box::new(move |frames| rule.apply(frames)))
+    }
+
+    /// Hide frames whose symbol name starts with `prefix`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .hide_symbol_prefix("tokio::runtime::")
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn hide_symbol_prefix<S: Into<String>>(self, prefix: S) -> Self {
+        self.add_frame_rule(FrameRule::HideSymbolPrefix(prefix.into()))
+    }
+
+    /// Hide frames of functions defined in the crate `name`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .hide_crate("hyper")
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn hide_crate<S: Into<String>>(self, name: S) -> Self {
+        self.add_frame_rule(FrameRule::HideCrate(name.into()))
+    }
+
+    /// Hide frames whose source file path matches the glob `pattern`, see
+    /// [`FrameRule::HidePath`] for the supported syntax
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .hide_path("**/.cargo/registry/**")
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn hide_path<S: Into<String>>(self, pattern: S) -> Self {
+        self.add_frame_rule(FrameRule::HidePath(pattern.into()))
+    }
+
+    /// Hide the frames of every crate except the given ones
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .keep_only_crates(&["my_app", "my_app_core"])
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn keep_only_crates<I>(self, names: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        let names = names.into_iter().map(|name| name.to_string()).collect();
+        self.add_frame_rule(FrameRule::KeepOnlyCrates(names))
+    }
+
+    /// Install the given Hook as the global error report hook
+    pub fn install(self) -> Result<(), crate::eyre::Report> {
+        let (panic_hook, eyre_hook) = self.try_into_hooks()?;
+        eyre_hook.install()?;
+        panic_hook.install();
+        Ok(())
+    }
+
+    /// Maps reports containing an error of type `E` to the exit code returned
+    /// by `rule` when they end `HookBuilder::run`
+    ///
+    /// # Details
+    ///
+    /// Rules are tried in the order they were added, and apply if `E` is the
+    /// error of the report, context added via `wrap_err` or any error in its
+    /// chain of sources. Reports no rule applies to exit with code 1.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// nocolor_eyre::config::HookBuilder::default()
+    ///     .exit_code_rule(|_: &std::io::Error| 74)
+    ///     .exit_code_rule(|_: &std::num::ParseIntError| 65)
+    ///     .install()
+    ///     .unwrap();
+    /// ```
+    pub fn exit_code_rule<E, F>(mut self, rule: F) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+        F: Fn(&E) -> u8 + Send + Sync + 'static,
+    {
+        self.exit_codes.add_rule(rule);
+        self
+    }
+
+    /// Configures the exit code `HookBuilder::run` exits with when the program
+    /// panics, 101 by default like the Rust runtime
+    pub fn panic_exit_code(mut self, code: u8) -> Self {
+        self.exit_codes.panic = code;
+        self
+    }
+
+    /// Install the hooks and run `main`, returning the exit code of the program
+    ///
+    /// # Details
+    ///
+    /// Unlike returning `eyre::Result` from `main`, reports are printed without
+    /// an `Error: ` prefix, and the exit code is chosen by the rules added via
+    /// `exit_code_rule`. Panics are reported by the panic hook as usual, after
+    /// which the program exits with the code set via `panic_exit_code`. An
+    /// error installing the hooks is reported like an error of `main`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nocolor_eyre::{config::HookBuilder, eyre::Result};
+    /// use std::process::ExitCode;
+    ///
+    /// fn main() -> ExitCode {
+    ///     HookBuilder::default()
+    ///         .exit_code_rule(|_: &std::io::Error| 74)
+    ///         .run(run)
+    /// }
+    ///
+    /// fn run() -> Result<()> {
+    ///     // ...
+    ///     # Ok(())
+    /// }
+    /// ```
+    pub fn run<F>(mut self, main: F) -> std::process::ExitCode
+    where
+        F: FnOnce() -> Result<(), crate::eyre::Report>,
+    {
+        let exit_codes = std::mem::take(&mut self.exit_codes);
+
+        let result = match self.install() {
+            // The program ends right after, so state left behind by the panic
+            // is never observed.
+            Ok(()) => match std::panic::catch_unwind(std::panic::AssertUnwindSafe(main)) {
+                Ok(result) => result,
+                // The panic hook has already reported the panic.
+                Err(_) => return std::process::ExitCode::from(exit_codes.panic),
+            },
+            Err(report) => Err(report),
+        };
+
+        match result {
+            Ok(()) => std::process::ExitCode::SUCCESS,
+            Err(report) => {
+                let rendered = format!("{:?}", report);
+                eprintln!("{}", rendered.trim_start_matches('\n'));
+                exit_codes.for_report(&report)
+            }
+        }
+    }
+
+    /// Add the default set of filters to this `HookBuilder`'s configuration
+    pub fn add_default_filters(self) -> Self {
+        self.add_frame_filter(Box::new(default_frame_filter))
+            .add_frame_filter(Box::new(eyre_frame_filters))
+    }
+
+    /// Create a `PanicHook` and `EyreHook` from this `HookBuilder`.
+    /// This can be used if you want to combine these handlers with other handlers.
+    pub fn into_hooks(self) -> (PanicHook, EyreHook) {
+        self.try_into_hooks().expect("into_hooks should only be called when no `color_spantrace` themes have previously been set")
+    }
+
+    /// Create a `PanicHook` and `EyreHook` from this `HookBuilder`.
+    /// This can be used if you want to combine these handlers with other handlers.
+    pub fn try_into_hooks(self) -> Result<(PanicHook, EyreHook), crate::eyre::Report> {
+        #[cfg(feature = "issue-url")]
+            let metadata = Arc::new(self.issue_metadata);
+        let max_crash_reports = self.max_crash_reports;
+        let crash_report_format = self.crash_report_format.unwrap_or(self.panic_format);
+        let display_thread_section = self.display_thread_section.unwrap_or(true);
+        let payloads = Arc::new(self.payload_formatters);
+        let paths = Arc::new(PathRewriter {
+            mappings: self.path_mappings,
+            workspace_root: self.workspace_root,
+            shorten: self.shorten_paths,
+            source_remaps: self.source_remaps,
+            source_roots: self.source_roots,
+            embedded_sources: self.embedded_sources,
+        });
+        let panic_hook = PanicHook {
+            filters: self.filters.into(),
+            collapse_recursion: self.collapse_recursion,
+            display_raw_addresses: self.display_raw_addresses,
+            ascii: self.ascii,
+            format: self.panic_format,
+            section: self.panic_section.map(Arc::from),
+            display_env_section: self.display_env_section,
+            #[cfg(feature = "track-caller")]
+            display_location_section: self.display_location_section,
+            display_thread_section,
+            #[cfg(feature = "tracing-error")]
+            capture_span_trace_by_default: self.capture_span_trace_by_default,
+            panic_message: self.panic_message.unwrap_or_else(|| {
+                Box::new(DefaultPanicMessage {
+                    paths: paths.clone(),
+                    payloads: payloads.clone(),
+                    display_thread_section,
+                })
+            }),
+            payloads,
+            paths: paths.clone(),
+            sinks: self.panic_sinks,
+            crash_reports: self.crash_report_dir.map(|dir| CrashReports {
+                dir,
+                max: max_crash_reports,
+                format: crash_report_format,
+            }),
+            #[cfg(feature = "issue-url")]
+            issue_url: self.issue_url.clone(),
+            #[cfg(feature = "issue-url")]
+            issue_metadata: metadata.clone(),
+            #[cfg(feature = "issue-url")]
+            issue_filter: self.issue_filter.clone(),
+        };
+
+        let eyre_hook = EyreHook {
+            filters: panic_hook.filters.clone(),
+            collapse_recursion: self.collapse_recursion,
+            display_raw_addresses: self.display_raw_addresses,
+            ascii: self.ascii,
+            capture_limiter: CaptureLimiter::new(self.capture_budget),
+            paths,
+            format: self.error_format,
+            display_env_section: self.display_env_section,
+            #[cfg(feature = "track-caller")]
+            display_location_section: self.display_location_section,
+            display_thread_section: self.display_thread_section.unwrap_or(false),
+            #[cfg(feature = "tracing-error")]
+            capture_span_trace_by_default: self.capture_span_trace_by_default,
+            #[cfg(feature = "issue-url")]
+            issue_url: self.issue_url,
+            #[cfg(feature = "issue-url")]
+            issue_metadata: metadata,
+            #[cfg(feature = "issue-url")]
+            issue_filter: self.issue_filter,
+        };
 
         Ok((panic_hook, eyre_hook))
     }
@@ -557,41 +1407,57 @@ fn eyre_frame_filters(frames: &mut Vec<&Frame>) {
     });
 }
 
-struct DefaultPanicMessage;
+struct DefaultPanicMessage {
+    paths: Arc<PathRewriter>,
+    payloads: Arc<PayloadFormatters>,
+    display_thread_section: bool,
+}
 
 impl PanicMessage for DefaultPanicMessage {
+    #[allow(deprecated)]
     fn display(&self, pi: &std::panic::PanicInfo<'_>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // XXX is my assumption correct that this function is guaranteed to only run after `nocolor_eyre` was setup successfully (including setting `THEME`), and that therefore the following line will never panic? Otherwise, we could return `fmt::Error`, but if the above is true, I like `unwrap` + a comment why this never fails better
         writeln!(f, "The application panicked (crashed)")?;
 
         // Print panic message.
-        let payload = pi
-            .payload()
-            .downcast_ref::<String>()
-            .map(String::as_str)
-            .or_else(|| pi.payload().downcast_ref::<&str>().cloned())
-            .unwrap_or("<non string panic payload>");
+        let payload = self.payloads.message(pi.payload());
 
         write!(f, "Message:  ")?;
         writeln!(f, "{}", payload)?;
 
         // If known, print panic location.
         write!(f, "Location: ")?;
-        write!(f, "{}", crate::fmt::LocationSection(pi.location()))?;
+        write!(
+            f,
+            "{}",
+            crate::fmt::LocationSection(pi.location(), &self.paths)
+        )?;
+
+        // The thread that panicked.
+        if self.display_thread_section {
+            write!(f, "\nThread:   ")?;
+            write!(f, "{}", ThreadSection::current())?;
+        }
 
         Ok(())
     }
 }
 
 /// A type representing an error report for a panic.
+#[allow(deprecated)]
 pub struct PanicReport<'a> {
-    hook: &'a PanicHook,
-    panic_info: &'a std::panic::PanicInfo<'a>,
-    backtrace: Option<backtrace::Backtrace>,
+    pub(crate) hook: &'a PanicHook,
+    pub(crate) panic_info: &'a std::panic::PanicInfo<'a>,
+    pub(crate) backtrace: Option<LazyBacktrace>,
+    #[cfg(feature = "tracing-error")]
+    pub(crate) span_trace: Option<tracing_error::SpanTrace>,
 }
 
 fn print_panic_info(report: &PanicReport<'_>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    report.hook.panic_message.display(report.panic_info, f)?;
+    let panic_message = &report.hook.panic_message;
+    // Panics can't be caught while the panic hook runs, so a panic in here
+    // aborts the process.
+    panic_message.display(report.panic_info, f)?;
 
     let v = panic_verbosity();
     let capture_bt = v != Verbosity::Minimal;
@@ -599,7 +1465,20 @@ fn print_panic_info(report: &PanicReport<'_>, f: &mut fmt::Formatter<'_>) -> fmt
     let mut separated = f.header("\n\n");
 
     if let Some(ref section) = report.hook.section {
-        write!(&mut separated.ready(), "{}", section)?;
+        write!(
+            &mut separated.ready(),
+            "{}",
+            Guarded::new(|f| write!(f, "{}", section))
+        )?;
+    }
+
+    #[cfg(feature = "tracing-error")]
+    if let Some(span_trace) = report.span_trace.as_ref() {
+        write!(
+            indented(&mut separated.ready()).with_format(Format::Uniform { indentation: "  " }),
+            "{}",
+            report.hook.format_span_trace(span_trace)
+        )?;
     }
 
     if let Some(bt) = report.backtrace.as_ref() {
@@ -614,49 +1493,191 @@ fn print_panic_info(report: &PanicReport<'_>, f: &mut fmt::Formatter<'_>) -> fmt
     if report.hook.display_env_section {
         let env_section = EnvSection {
             bt_captured: &capture_bt,
+            bt_rate_limited: false,
         };
 
         write!(&mut separated.ready(), "{}", env_section)?;
     }
 
     #[cfg(feature = "issue-url")]
-    {
-        let payload = report.panic_info.payload();
+    if let Some(issue_section) = report.issue_section() {
+        write!(&mut separated.ready(), "{}", issue_section)?;
+    }
 
-        if report.hook.issue_url.is_some()
-            && (*report.hook.issue_filter)(crate::ErrorKind::NonRecoverable(payload))
-        {
-            let url = report.hook.issue_url.as_ref().unwrap();
-            let payload = payload
-                .downcast_ref::<String>()
-                .map(String::as_str)
-                .or_else(|| payload.downcast_ref::<&str>().cloned())
-                .unwrap_or("<non string panic payload>");
+    Ok(())
+}
 
-            let issue_section = crate::section::github::IssueSection::new(url, payload)
-                .with_backtrace(report.backtrace.as_ref())
-                .with_location(report.panic_info.location())
-                .with_metadata(&**report.hook.issue_metadata);
+impl PanicReport<'_> {
+    /// The message of the panic, rendered with the formatters registered via
+    /// `HookBuilder::panic_payload_formatter` for non string payloads
+    pub fn payload_message(&self) -> Cow<'_, str> {
+        self.hook.payloads.message(self.panic_info.payload())
+    }
 
-            write!(&mut separated.ready(), "{}", issue_section)?;
+    /// The location section of the panic, unless disabled via
+    /// `HookBuilder::display_location_section`
+    pub(crate) fn location_section(&self) -> Option<crate::fmt::LocationSection<'_>> {
+        #[cfg(feature = "track-caller")]
+        if !self.hook.display_location_section {
+            return None;
         }
+
+        Some(crate::fmt::LocationSection(
+            self.panic_info.location(),
+            &self.hook.paths,
+        ))
     }
 
-    Ok(())
+    /// The thread that panicked, unless disabled via
+    /// `HookBuilder::display_thread_section`
+    pub(crate) fn thread_section(&self) -> Option<ThreadSection> {
+        if self.hook.display_thread_section {
+            Some(ThreadSection::current())
+        } else {
+            None
+        }
+    }
+
+    /// Render the report as a standalone HTML document, regardless of the
+    /// configured `ReportFormat`
+    ///
+    /// Returns an empty string if the report fails to render.
+    pub fn html(&self) -> String {
+        crate::html::panic_report(self).unwrap_or_default()
+    }
+
+    fn fmt_as(&self, format: ReportFormat, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match format {
+            ReportFormat::Text => print_panic_info(self, f),
+            #[cfg(feature = "json")]
+            ReportFormat::Json => write!(f, "{}", crate::json::panic_report(self)),
+            ReportFormat::Markdown => f.write_str(&crate::markdown::panic_report(self)?),
+            ReportFormat::Html => f.write_str(&crate::html::panic_report(self)?),
+        }
+    }
+
+    /// The issue url section for this panic, if issue reporting is configured
+    /// and not filtered out for this panic
+    #[cfg(feature = "issue-url")]
+    pub(crate) fn issue_section(&self) -> Option<crate::section::github::IssueSection<'_>> {
+        let payload = self.panic_info.payload();
+        let message = self.payload_message();
+
+        let url = self.hook.issue_url.as_ref().filter(|_| {
+            (*self.hook.issue_filter)(crate::ErrorKind::NonRecoverable(payload, &message))
+        })?;
+
+        let issue_section = crate::section::github::IssueSection::new(url, message)
+            .with_backtrace(self.backtrace.as_ref().map(LazyBacktrace::resolved))
+            .with_location(self.panic_info.location())
+            .with_metadata(&self.hook.issue_metadata);
+
+        Some(issue_section)
+    }
 }
 
 impl Display for PanicReport<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        print_panic_info(self, f)
+        self.fmt_as(self.hook.format, f)
+    }
+}
+
+/// Displays a panic report in a format other than the configured one
+struct InFormat<'a, 'b>(&'a PanicReport<'b>, ReportFormat);
+
+impl Display for InFormat<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_as(self.1, f)
+    }
+}
+
+enum PanicSink {
+    Writer(Mutex<Box<dyn io::Write + Send + 'static>>),
+    Callback(Box<PanicCallback>),
+}
+
+/// The payload of a panic if it is a string, without running any user
+/// supplied payload formatters
+#[allow(deprecated)]
+fn payload_str<'a>(panic_info: &'a std::panic::PanicInfo<'_>) -> &'a str {
+    panic_info
+        .payload()
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| panic_info.payload().downcast_ref::<&str>().cloned())
+        .unwrap_or("<non string panic payload>")
+}
+
+thread_local! {
+    static REPORTING_PANIC: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as reporting a panic until dropped
+struct ReportingGuard;
+
+impl ReportingGuard {
+    /// Returns `None` if the current thread is already reporting a panic
+    fn enter() -> Option<Self> {
+        // Thread locals may already be destroyed when panicking during thread
+        // exit, treat that as not reporting.
+        let reporting = REPORTING_PANIC
+            .try_with(|reporting| reporting.replace(true))
+            .unwrap_or(false);
+
+        if reporting {
+            None
+        } else {
+            Some(Self)
+        }
     }
 }
 
+impl Drop for ReportingGuard {
+    fn drop(&mut self) {
+        let _ = REPORTING_PANIC.try_with(|reporting| reporting.set(false));
+    }
+}
+
+/// Report a panic raised while the panic hook was already reporting a panic
+///
+/// Only the payload and location are printed, without running any user
+/// supplied code, so that this can't panic again.
+#[allow(deprecated)]
+fn print_fallback_report(panic_info: &std::panic::PanicInfo<'_>) {
+    let location = match panic_info.location() {
+        Some(location) => location.to_string(),
+        None => "<unknown>".to_owned(),
+    };
+
+    eprintln!(
+        "The application panicked (crashed) while the panic hook was reporting another panic\n\
+         Message:  {}\n\
+         Location: {}\n\n\
+         The panic hook itself panicked, so only this minimal report is shown for the nested panic.",
+        payload_str(panic_info),
+        location
+    );
+}
+
 /// A panic reporting hook
 pub struct PanicHook {
     filters: Arc<[Box<FilterCallback>]>,
-    section: Option<Box<dyn Display + Send + Sync + 'static>>,
+    collapse_recursion: bool,
+    display_raw_addresses: bool,
+    ascii: bool,
+    pub(crate) paths: Arc<PathRewriter>,
+    format: ReportFormat,
+    pub(crate) section: Option<Arc<dyn Display + Send + Sync + 'static>>,
     panic_message: Box<dyn PanicMessage>,
-    display_env_section: bool,
+    payloads: Arc<PayloadFormatters>,
+    sinks: Vec<PanicSink>,
+    crash_reports: Option<CrashReports>,
+    pub(crate) display_env_section: bool,
+    #[cfg(feature = "track-caller")]
+    display_location_section: bool,
+    display_thread_section: bool,
+    #[cfg(feature = "tracing-error")]
+    capture_span_trace_by_default: bool,
     #[cfg(feature = "issue-url")]
     issue_url: Option<String>,
     #[cfg(feature = "issue-url")]
@@ -668,30 +1689,120 @@ pub struct PanicHook {
 impl PanicHook {
     pub(crate) fn format_backtrace<'a>(
         &'a self,
-        trace: &'a backtrace::Backtrace,
+        trace: &'a LazyBacktrace,
     ) -> BacktraceFormatter<'a> {
         BacktraceFormatter {
             filters: &self.filters,
-            inner: trace,
+            collapse_recursion: self.collapse_recursion,
+            display_raw_addresses: self.display_raw_addresses,
+            charset: Charset::new(self.ascii),
+            paths: &self.paths,
+            inner: trace.resolved(),
         }
     }
 
+    #[cfg(feature = "tracing-error")]
+    pub(crate) fn format_span_trace<'a>(
+        &'a self,
+        span_trace: &'a tracing_error::SpanTrace,
+    ) -> crate::spantrace::SpanTraceFormatter<'a> {
+        crate::spantrace::SpanTraceFormatter(span_trace, &self.paths, Charset::new(self.ascii))
+    }
+
     /// Install self as a global panic hook via `std::panic::set_hook`.
     pub fn install(self) {
         std::panic::set_hook(self.into_panic_hook());
     }
 
     /// Convert self into the type expected by `std::panic::set_hook`.
+    ///
+    /// If the hook is invoked again on a thread that is already reporting a
+    /// panic, for example by a `PanicMessage` or panic callback that calls
+    /// the hook itself, it prints a minimal fallback report for the nested
+    /// panic instead of recursing. A panic raised while the hook runs aborts
+    /// the process before the hook is invoked again, as the standard library
+    /// doesn't run panic hooks for it.
+    ///
+    /// Panics raised by a section while an error report is being rendered
+    /// aren't reported, the report shows a placeholder in place of the
+    /// section instead.
+    #[allow(deprecated)]
     pub fn into_panic_hook(
         self,
     ) -> Box<dyn Fn(&std::panic::PanicInfo<'_>) + Send + Sync + 'static> {
         Box::new(move |panic_info| {
-            eprintln!("{}", self.panic_report(panic_info));
+            let _guard = match ReportingGuard::enter() {
+                Some(guard) => guard,
+                None => return print_fallback_report(panic_info),
+            };
+
+            // Panics in user supplied sections are rendered as a placeholder
+            // by the report containing them.
+            if crate::writers::guarding() {
+                return;
+            }
+
+            let report = self.panic_report(panic_info);
+            let rendered = report.to_string();
+
+            if self.sinks.is_empty() {
+                eprintln!("{}", rendered);
+            }
+
+            for sink in &self.sinks {
+                match sink {
+                    PanicSink::Writer(writer) => {
+                        let mut writer = writer.lock().unwrap_or_else(PoisonError::into_inner);
+                        // There is nowhere left to report a failure to write a
+                        // panic report to.
+                        let _ = writeln!(writer, "{}", rendered).and_then(|_| writer.flush());
+                    }
+                    PanicSink::Callback(callback) => callback(&report),
+                }
+            }
+
+            if let Some(crash_reports) = self.crash_reports.as_ref() {
+                let crash_report = if crash_reports.format == self.format {
+                    rendered
+                } else {
+                    InFormat(&report, crash_reports.format).to_string()
+                };
+
+                match crash_reports.write(&crash_report) {
+                    Ok(path) => eprintln!("A crash report was written to {}", path.display()),
+                    Err(e) => eprintln!(
+                        "Failed to write a crash report to {}: {}",
+                        crash_reports.dir.display(),
+                        e
+                    ),
+                }
+            }
+
+            if crate::catch::catching() {
+                self.capture(report);
+            }
         })
     }
 
+    /// Hand the details of a panic to the enclosing `catch_panic` call
+    ///
+    /// The backtrace and `SpanTrace` of the already delivered report are
+    /// reused for the report `catch_panic` returns.
+    fn capture(&self, report: PanicReport<'_>) {
+        crate::catch::store(crate::catch::CaughtPanic {
+            message: report.payload_message().into_owned(),
+            location: report.panic_info.location().map(Into::into),
+            thread: ThreadSection::current(),
+            section: self.section.clone(),
+            backtrace: report.backtrace,
+            #[cfg(feature = "tracing-error")]
+            span_trace: report.span_trace,
+        });
+    }
+
     /// Construct a panic reporter which prints it's panic report via the
     /// `Display` trait.
+    #[allow(deprecated)]
     pub fn panic_report<'a>(
         &'a self,
         panic_info: &'a std::panic::PanicInfo<'_>,
@@ -700,7 +1811,14 @@ impl PanicHook {
         let capture_bt = v != Verbosity::Minimal;
 
         let backtrace = if capture_bt {
-            Some(backtrace::Backtrace::new())
+            Some(LazyBacktrace::capture())
+        } else {
+            None
+        };
+
+        #[cfg(feature = "tracing-error")]
+        let span_trace = if spantrace_capture_enabled(self.capture_span_trace_by_default) {
+            Some(tracing_error::SpanTrace::capture())
         } else {
             None
         };
@@ -708,6 +1826,8 @@ impl PanicHook {
         PanicReport {
             panic_info,
             backtrace,
+            #[cfg(feature = "tracing-error")]
+            span_trace,
             hook: self,
         }
     }
@@ -716,9 +1836,18 @@ impl PanicHook {
 /// An eyre reporting hook used to construct `EyreHandler`s
 pub struct EyreHook {
     filters: Arc<[Box<FilterCallback>]>,
+    collapse_recursion: bool,
+    display_raw_addresses: bool,
+    ascii: bool,
+    capture_limiter: CaptureLimiter,
+    paths: Arc<PathRewriter>,
+    format: ReportFormat,
     display_env_section: bool,
     #[cfg(feature = "track-caller")]
     display_location_section: bool,
+    display_thread_section: bool,
+    #[cfg(feature = "tracing-error")]
+    capture_span_trace_by_default: bool,
     #[cfg(feature = "issue-url")]
     issue_url: Option<String>,
     #[cfg(feature = "issue-url")]
@@ -737,20 +1866,85 @@ type HookFunc = Box<
 impl EyreHook {
     #[allow(unused_variables)]
     pub(crate) fn default(&self, error: &(dyn std::error::Error + 'static)) -> crate::Handler {
-        let backtrace = if lib_verbosity() != Verbosity::Minimal {
-            Some(backtrace::Backtrace::new())
+        let caught = error
+            .downcast_ref::<crate::catch::PanicError>()
+            .and_then(crate::catch::PanicError::take_caught);
+        if let Some(caught) = caught {
+            return self.panic_handler(caught);
+        }
+
+        let mut backtrace_rate_limited = false;
+        let backtrace = if lib_verbosity() == Verbosity::Minimal {
+            None
+        } else if self.capture_limiter.try_acquire() {
+            Some(LazyBacktrace::capture())
+        } else {
+            backtrace_rate_limited = true;
+            None
+        };
+
+        #[cfg(feature = "tracing-error")]
+        let span_trace = if spantrace_capture_enabled(self.capture_span_trace_by_default)
+            && crate::spantrace::get_deepest_spantrace(error).is_none()
+        {
+            Some(tracing_error::SpanTrace::capture())
         } else {
             None
         };
 
+        let mut handler = self.handler();
+        handler.backtrace = backtrace;
+        handler.backtrace_rate_limited = backtrace_rate_limited;
+        #[cfg(feature = "tracing-error")]
+        {
+            handler.span_trace = span_trace;
+        }
+        handler
+    }
+
+    /// Construct the handler of a report created by `catch_panic`, from the
+    /// details of the panic captured by the panic hook
+    fn panic_handler(&self, caught: crate::catch::CaughtPanic) -> crate::Handler {
+        let mut handler = self.handler();
+        handler.backtrace = caught.backtrace;
+        #[cfg(feature = "tracing-error")]
+        {
+            handler.span_trace = caught.span_trace;
+        }
+        handler.panic_location = caught.location;
+        if self.display_thread_section {
+            handler.thread = Some(caught.thread);
+        }
+        if let Some(section) = caught.section {
+            handler.sections.push(HelpInfo::Custom(Box::new(section)));
+        }
+        handler
+    }
+
+    /// A handler without a backtrace or `SpanTrace`
+    fn handler(&self) -> crate::Handler {
         crate::Handler {
             filters: self.filters.clone(),
-            backtrace,
+            collapse_recursion: self.collapse_recursion,
+            display_raw_addresses: self.display_raw_addresses,
+            ascii: self.ascii,
+            paths: self.paths.clone(),
+            format: self.format,
+            backtrace: None,
+            backtrace_rate_limited: false,
+            #[cfg(feature = "tracing-error")]
+            span_trace: None,
             suppress_backtrace: false,
             sections: Vec::new(),
             display_env_section: self.display_env_section,
             #[cfg(feature = "track-caller")]
             display_location_section: self.display_location_section,
+            thread: if self.display_thread_section {
+                Some(ThreadSection::current())
+            } else {
+                None
+            },
+            panic_location: None,
             #[cfg(feature = "issue-url")]
             issue_url: self.issue_url.clone(),
             #[cfg(feature = "issue-url")]
@@ -775,38 +1969,95 @@ impl EyreHook {
 
 pub(crate) struct BacktraceFormatter<'a> {
     pub(crate) filters: &'a [Box<FilterCallback>],
+    pub(crate) collapse_recursion: bool,
+    pub(crate) display_raw_addresses: bool,
+    pub(crate) charset: Charset,
+    pub(crate) paths: &'a PathRewriter,
     pub(crate) inner: &'a backtrace::Backtrace,
 }
 
-impl Display for BacktraceFormatter<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[BACKTRACE]")?;
+impl BacktraceFormatter<'_> {
+    /// Collect frame info.
+    ///
+    /// Every symbol becomes its own `Frame`, with inlined symbols preceding the
+    /// symbol of the function they were inlined into. Physical frames without
+    /// any symbols are kept as a single unnamed `Frame`.
+    pub(crate) fn frames(&self) -> Vec<Frame> {
+        let mut frames = Vec::new();
+
+        for (index, frame) in self.inner.frames().iter().enumerate() {
+            let ip = Some(frame.ip() as usize);
+            let module_base = frame.module_base_address().map(|base| base as usize);
+            let symbols = frame.symbols();
+
+            if symbols.is_empty() {
+                frames.push(Frame {
+                    n: frames.len() + 1,
+                    index,
+                    inlined: false,
+                    name: None,
+                    lineno: None,
+                    colno: None,
+                    filename: None,
+                    ip,
+                    module_base,
+                });
+            }
 
-        // Collect frame info.
-        let frames: Vec<_> = self
-            .inner
-            .frames()
-            .iter()
-            .flat_map(|frame| frame.symbols())
-            .zip(1usize..)
-            .map(|(sym, n)| Frame {
-                name: sym.name().map(|x| x.to_string()),
-                lineno: sym.lineno(),
-                filename: sym.filename().map(|x| x.into()),
-                n,
-            })
-            .collect();
+            for (i, sym) in symbols.iter().enumerate() {
+                frames.push(Frame {
+                    n: frames.len() + 1,
+                    index,
+                    inlined: i + 1 < symbols.len(),
+                    name: sym.name().map(|x| x.to_string()),
+                    lineno: sym.lineno(),
+                    colno: sym.colno(),
+                    filename: sym.filename().map(|x| x.into()),
+                    ip,
+                    module_base,
+                });
+            }
+        }
+
+        frames
+    }
 
+    /// Apply the configured frame filters to `frames`, unless frame filtering
+    /// has been disabled via `COLORBT_SHOW_HIDDEN`, followed by the overrides
+    /// from `NOCOLOR_EYRE_HIDE` and `NOCOLOR_EYRE_SHOW`.
+    pub(crate) fn filter<'f>(&self, frames: &'f [Frame]) -> Vec<&'f Frame> {
         let mut filtered_frames = frames.iter().collect();
-        match env::var("COLORBT_SHOW_HIDDEN").ok().as_deref() {
-            Some("1") | Some("on") | Some("y") => (),
-            _ => {
-                for filter in self.filters {
-                    filter(&mut filtered_frames);
+        if !show_hidden() {
+            for filter in self.filters {
+                filter(&mut filtered_frames);
+            }
+        }
+
+        crate::filter::apply_env_overrides(frames, &mut filtered_frames);
+
+        // Without symbol names filters can't tell frames apart, so keep all
+        // of them when they are meant to be symbolized later.
+        if self.display_raw_addresses {
+            for frame in frames.iter().filter(|frame| frame.name.is_none()) {
+                if !filtered_frames.iter().any(|f| f.n == frame.n) {
+                    filtered_frames.push(frame);
                 }
             }
         }
 
+        // Don't let filters mess with the order.
+        filtered_frames.sort_by_key(|x| x.n);
+        filtered_frames
+    }
+}
+
+impl Display for BacktraceFormatter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[BACKTRACE]")?;
+
+        let frames = self.frames();
+        let filtered_frames = self.filter(&frames);
+
         if filtered_frames.is_empty() {
             // TODO: Would probably look better centered.
             return write!(f, "\n<empty backtrace>");
@@ -814,47 +2065,246 @@ impl Display for BacktraceFormatter<'_> {
 
         let mut separated = f.header("\n");
 
-        // Don't let filters mess with the order.
-        filtered_frames.sort_by_key(|x| x.n);
-
         let mut buf = String::new();
 
+        let collapse_recursion = self.collapse_recursion && !show_hidden();
+        let modules = if self.display_raw_addresses {
+            Modules::load()
+        } else {
+            Modules::default()
+        };
+
+        // Counts the physical frames in the gap between two printed symbols,
+        // not counting the frames those symbols belong to.
+        let hidden_frames = |prev: Option<&Frame>, next: Option<&Frame>| {
+            let start = prev.map_or(0, |frame| frame.n);
+            let end = next.map_or(frames.len(), |frame| frame.n - 1);
+            let mut indices: Vec<_> = frames[start..end]
+                .iter()
+                .map(|frame| frame.index)
+                .filter(|&index| {
+                    prev.map_or(true, |frame| frame.index != index)
+                        && next.map_or(true, |frame| frame.index != index)
+                })
+                .collect();
+            indices.dedup();
+            indices.len()
+        };
+
         macro_rules! print_hidden {
             ($n:expr) => {
                 let n = $n;
                 buf.clear();
-                write!(
-                    &mut buf,
-                    "{decorator} {n} frame{plural} hidden {decorator}",
-                    n = n,
-                    plural = if n == 1 { "" } else { "s" },
-                    decorator = "⋮",
-                )
+                match self.charset {
+                    Charset::Unicode => write!(
+                        &mut buf,
+                        "{decorator} {n} frame{plural} hidden {decorator}",
+                        n = n,
+                        plural = if n == 1 { "" } else { "s" },
+                        decorator = "⋮",
+                    ),
+                    Charset::Ascii => write!(&mut buf, "hidden frames: {}", n),
+                }
                 .expect("writing to strings doesn't panic");
-                write!(&mut separated.ready(), "{:^80}", buf)?;
+                self.charset.write_marker(&mut separated.ready(), &buf)?;
             };
         }
 
-        let mut last_n = 0;
-        for frame in &filtered_frames {
-            let frame_delta = frame.n - last_n - 1;
-            if frame_delta != 0 {
-                print_hidden!(frame_delta);
+        macro_rules! print_repeated {
+            ($len:expr, $times:expr) => {
+                let (len, times) = ($len, $times);
+                buf.clear();
+                match self.charset {
+                    Charset::Unicode => write!(
+                        &mut buf,
+                        "{decorator} previous {len} frame{plural} repeated {times} time{times_plural} {decorator}",
+                        len = len,
+                        plural = if len == 1 { "" } else { "s" },
+                        times = times,
+                        times_plural = if times == 1 { "" } else { "s" },
+                        decorator = "⋮",
+                    ),
+                    Charset::Ascii => write!(
+                        &mut buf,
+                        "repeated frames: previous {len} frame{plural} repeated {times} time{times_plural}",
+                        len = len,
+                        plural = if len == 1 { "" } else { "s" },
+                        times = times,
+                        times_plural = if times == 1 { "" } else { "s" },
+                    ),
+                }
+                .expect("writing to strings doesn't panic");
+                self.charset.write_marker(&mut separated.ready(), &buf)?;
+            };
+        }
+
+        let cycles = if collapse_recursion {
+            repeated_cycles(&filtered_frames)
+        } else {
+            Vec::new()
+        };
+
+        let mut last: Option<&Frame> = None;
+        // Hidden frames not printed yet, including the ones between the
+        // repetitions of a collapsed cycle.
+        let mut hidden = 0;
+        let mut i = 0;
+        while i < filtered_frames.len() {
+            let (len, times) = cycles.get(i).copied().flatten().unwrap_or((1, 0));
+
+            for (j, &frame) in filtered_frames[i..i + len].iter().enumerate() {
+                hidden += hidden_frames(last, Some(frame));
+                if hidden != 0 {
+                    print_hidden!(hidden);
+                    hidden = 0;
+                }
+                let continued =
+                    last.is_some_and(|last| last.index == frame.index && last.n + 1 == frame.n);
+                let ends_frame = filtered_frames.get(i + j + 1).map_or(true, |next| {
+                    next.index != frame.index || next.n != frame.n + 1
+                });
+                let raw_address = match frame.ip {
+                    Some(ip) if self.display_raw_addresses && ends_frame => Some(RawAddress {
+                        ip,
+                        module_base: frame.module_base,
+                        module: modules.find(ip),
+                    }),
+                    _ => None,
+                };
+                write!(
+                    &mut separated.ready(),
+                    "{}",
+                    StyledFrame {
+                        frame,
+                        continued,
+                        raw_address,
+                        charset: self.charset,
+                        paths: self.paths,
+                    }
+                )?;
+                last = Some(frame);
             }
-            write!(&mut separated.ready(), "{}", StyledFrame(frame))?;
-            last_n = frame.n;
+
+            if times != 0 {
+                print_repeated!(len, times);
+            }
+
+            let end = i + len * (times + 1);
+            for pair in filtered_frames[i + len - 1..end].windows(2) {
+                hidden += hidden_frames(Some(pair[0]), Some(pair[1]));
+            }
+            i = end;
+            last = Some(filtered_frames[i - 1]);
         }
 
-        let last_filtered_n = filtered_frames.last().unwrap().n;
-        let last_unfiltered_n = frames.last().unwrap().n;
-        if last_filtered_n < last_unfiltered_n {
-            print_hidden!(last_unfiltered_n - last_filtered_n);
+        hidden += hidden_frames(last, None);
+        if hidden != 0 {
+            print_hidden!(hidden);
         }
 
         Ok(())
     }
 }
 
+/// The longest cycle of frames considered when collapsing recursion
+const MAX_CYCLE_LEN: usize = 64;
+/// How often a cycle of frames must occur in a row before it is collapsed
+const MIN_CYCLE_COUNT: usize = 3;
+
+/// Find the cycle of frames starting at each position of `frames` that
+/// repeats the most frames, as its length and how often it is repeated after
+/// its first occurrence
+///
+/// Every cycle length is checked in a single pass over the frames, so this
+/// takes linear time in the number of frames.
+fn repeated_cycles(frames: &[&Frame]) -> Vec<Option<(usize, usize)>> {
+    let same = |a: &Frame, b: &Frame| {
+        a.ip == b.ip && a.name == b.name && a.filename == b.filename && a.lineno == b.lineno
+    };
+
+    let covered = |(len, times): (usize, usize)| len * (times + 1);
+
+    // Prefer the shortest cycle among those covering the most frames, by
+    // only replacing a cycle found for a shorter length by a longer one that
+    // covers more frames.
+    let mut cycles = vec![None; frames.len()];
+    // The number of frames from each position on that equal the frame `len`
+    // positions further.
+    let mut matching = vec![0; frames.len() + 1];
+    for len in 1..=MAX_CYCLE_LEN.min(frames.len() / MIN_CYCLE_COUNT) {
+        matching[frames.len() - len] = 0;
+        for i in (0..frames.len() - len).rev() {
+            matching[i] = if same(frames[i], frames[i + len]) {
+                matching[i + 1] + 1
+            } else {
+                0
+            };
+
+            let times = matching[i] / len;
+            if times + 1 >= MIN_CYCLE_COUNT
+                && cycles[i].map_or(true, |cycle| covered(cycle) < covered((len, times)))
+            {
+                cycles[i] = Some((len, times));
+            }
+        }
+    }
+
+    cycles
+}
+
+/// Environment variable overriding `HookBuilder::ascii_output`
+const ASCII_ENV: &str = "NOCOLOR_EYRE_ASCII";
+
+/// The characters used for the decorations of backtraces and source snippets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Charset {
+    Unicode,
+    Ascii,
+}
+
+impl Charset {
+    /// The charset selected via `HookBuilder::ascii_output`, unless overridden
+    /// by `NOCOLOR_EYRE_ASCII`
+    pub(crate) fn new(ascii: bool) -> Self {
+        let ascii = match env::var(ASCII_ENV).ok().as_deref() {
+            Some("1") | Some("on") | Some("y") => true,
+            Some("0") | Some("off") | Some("n") => false,
+            _ => ascii,
+        };
+
+        if ascii {
+            Charset::Ascii
+        } else {
+            Charset::Unicode
+        }
+    }
+
+    /// The separator between line numbers and lines of source snippets
+    fn gutter(self) -> &'static str {
+        match self {
+            Charset::Unicode => "│",
+            Charset::Ascii => "|",
+        }
+    }
+
+    /// Write a line standing in for frames left out of a backtrace
+    fn write_marker(self, f: &mut dyn fmt::Write, marker: &str) -> fmt::Result {
+        match self {
+            Charset::Unicode => write!(f, "{:^80}", marker),
+            // Centering only adds noise for screen readers, indent the marker
+            // like the source location of a frame instead.
+            Charset::Ascii => write!(f, "    {}", marker),
+        }
+    }
+}
+
+fn show_hidden() -> bool {
+    matches!(
+        env::var("COLORBT_SHOW_HIDDEN").ok().as_deref(),
+        Some("1") | Some("on") | Some("y")
+    )
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub(crate) enum Verbosity {
     Minimal,
@@ -878,6 +2328,35 @@ pub(crate) fn lib_verbosity() -> Verbosity {
     }
 }
 
+/// The conventional directory for crash reports of the application `app_name`
+///
+/// This is `$XDG_STATE_HOME/<app_name>/crashes`, falling back to
+/// `$HOME/.local/state/<app_name>/crashes` when `XDG_STATE_HOME` is not set, or
+/// `%LOCALAPPDATA%\<app_name>\crashes` on windows. Returns `None` if none of
+/// these variables are set.
+pub fn default_crash_report_dir(app_name: &str) -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_STATE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/state")))
+    };
+
+    base.map(|base| base.join(app_name).join("crashes"))
+}
+
+#[cfg(feature = "tracing-error")]
+pub(crate) fn spantrace_capture_enabled(default: bool) -> bool {
+    env::var("RUST_SPANTRACE")
+        .map(|val| val != "0")
+        .unwrap_or(default)
+}
+
+/// Callback receiving panic reports, see `HookBuilder::add_panic_callback`
+pub type PanicCallback = dyn Fn(&PanicReport<'_>) + Send + Sync + 'static;
+
 /// Callback for filtering a vector of `Frame`s
 pub type FilterCallback = dyn Fn(&mut Vec<&Frame>) + Send + Sync + 'static;
 
@@ -0,0 +1,60 @@
+//! Crash report files written by the panic hook
+use crate::config::ReportFormat;
+use std::{
+    fs, io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const PREFIX: &str = "crash-";
+
+pub(crate) struct CrashReports {
+    pub(crate) dir: PathBuf,
+    pub(crate) max: usize,
+    pub(crate) format: ReportFormat,
+}
+
+impl CrashReports {
+    /// Write `report` to a new timestamped file and prune the oldest reports
+    pub(crate) fn write(&self, report: &str) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+
+        // Fixed width timestamps keep the lexicographic order of the file names
+        // chronological.
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let path = self.dir.join(format!(
+            "{}{:010}.{:09}-{}.{}",
+            PREFIX,
+            now.as_secs(),
+            now.subsec_nanos(),
+            std::process::id(),
+            self.format.extension()
+        ));
+
+        fs::write(&path, report)?;
+        // The report is written at this point, failing to remove old reports
+        // shouldn't make it look like it wasn't.
+        let _ = self.prune();
+
+        Ok(path)
+    }
+
+    fn prune(&self) -> io::Result<()> {
+        let mut reports: Vec<_> = fs::read_dir(&self.dir)?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(PREFIX))
+            .map(|entry| entry.path())
+            .collect();
+
+        reports.sort();
+
+        let excess = reports.len().saturating_sub(self.max.max(1));
+        for path in &reports[..excess] {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+//! Mapping of errors and panics to process exit codes, see `HookBuilder::run`
+use eyre::Report;
+use std::process::ExitCode;
+
+/// Rule mapping a report to an exit code, if it applies to the report
+type ExitCodeRule = dyn Fn(&Report) -> Option<u8> + Send + Sync + 'static;
+
+/// The exit codes used by `HookBuilder::run`
+pub(crate) struct ExitCodes {
+    rules: Vec<Box<ExitCodeRule>>,
+    pub(crate) panic: u8,
+}
+
+impl Default for ExitCodes {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            // The exit code of the default panic runtime when the main thread
+            // panics.
+            panic: 101,
+        }
+    }
+}
+
+impl ExitCodes {
+    pub(crate) fn add_rule<E, F>(&mut self, rule: F)
+    where
+        E: std::error::Error + Send + Sync + 'static,
+        F: Fn(&E) -> u8 + Send + Sync + 'static,
+    {
+        self.rules.push(Box::new(move |report| {
+            // `Report::downcast_ref` also finds context added via `wrap_err`,
+            // the chain finds errors further down.
+            let error = report
+                .downcast_ref::<E>()
+                .or_else(|| report.chain().find_map(|error| error.downcast_ref::<E>()))?;
+            Some(rule(error))
+        }));
+    }
+
+    /// The exit code for `report`, from the first rule that applies to it
+    pub(crate) fn for_report(&self, report: &Report) -> ExitCode {
+        let code = self.rules.iter().find_map(|rule| rule(report)).unwrap_or(1);
+        ExitCode::from(code)
+    }
+}
@@ -0,0 +1,184 @@
+//! Declarative rules for filtering backtrace frames
+use crate::config::Frame;
+
+/// A declarative rule for hiding frames from backtraces
+///
+/// # Details
+///
+/// Rules are added to a `HookBuilder` via `add_frame_rule` or one of its
+/// shorthands, and are applied alongside the `FilterCallback`s added via
+/// `add_frame_filter`. Crate names may be given with either dashes or
+/// underscores.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FrameRule {
+    /// Hide frames whose symbol name starts with the given prefix
+    HideSymbolPrefix(String),
+    /// Hide frames of functions defined in the given crate
+    HideCrate(String),
+    /// Hide frames whose source file path matches the given glob pattern
+    ///
+    /// `*` matches any sequence of characters except `/`, `**` matches any
+    /// sequence of characters including `/`, and `?` matches a single
+    /// character. Windows path separators are matched as `/`.
+    HidePath(String),
+    /// Hide every run of frames starting at a frame whose symbol name starts
+    /// with `start` up to and including the next frame whose symbol name starts
+    /// with `end`
+    ///
+    /// A start marker without a matching end marker hides nothing.
+    HideBetween {
+        /// Symbol prefix of the first hidden frame
+        start: String,
+        /// Symbol prefix of the last hidden frame
+        end: String,
+    },
+    /// Hide the frames of every crate except the given ones
+    KeepOnlyCrates(Vec<String>),
+}
+
+impl FrameRule {
+    /// Apply this rule to a set of frames, removing the hidden ones
+    pub fn apply(&self, frames: &mut Vec<&Frame>) {
+        match self {
+            FrameRule::HideSymbolPrefix(prefix) => {
+                frames.retain(|frame| !has_symbol_prefix(frame, prefix))
+            }
+            FrameRule::HideCrate(name) => {
+                let name = normalize_crate_name(name);
+                frames.retain(|frame| frame.crate_name() != Some(&name))
+            }
+            FrameRule::HidePath(pattern) => frames.retain(|frame| {
+                let path = match frame.filename.as_ref() {
+                    Some(path) => path.to_string_lossy().replace('\\', "/"),
+                    None => return true,
+                };
+
+                !glob_match(pattern.as_bytes(), path.as_bytes())
+            }),
+            FrameRule::HideBetween { start, end } => {
+                let mut hidden = vec![false; frames.len()];
+                let mut i = 0;
+
+                while i < frames.len() {
+                    if has_symbol_prefix(frames[i], start) {
+                        let len = frames[i..]
+                            .iter()
+                            .position(|frame| has_symbol_prefix(frame, end));
+
+                        if let Some(len) = len {
+                            hidden[i..=i + len].iter_mut().for_each(|h| *h = true);
+                            i += len;
+                        }
+                    }
+
+                    i += 1;
+                }
+
+                let mut hidden = hidden.into_iter();
+                frames.retain(|_| !hidden.next().unwrap_or(false));
+            }
+            FrameRule::KeepOnlyCrates(names) => {
+                let names: Vec<_> = names
+                    .iter()
+                    .map(|name| normalize_crate_name(name))
+                    .collect();
+                frames.retain(|frame| match frame.crate_name() {
+                    Some(name) => names.iter().any(|n| n == name),
+                    None => false,
+                })
+            }
+        }
+    }
+}
+
+impl Frame {
+    /// The name of the crate the function of this frame is defined in, as far
+    /// as it can be determined from the symbol name
+    ///
+    /// For trait impls like `<alloc::boxed::Box<F> as core::ops::FnOnce>::call_once`
+    /// this is the crate of the implementing type.
+    pub fn crate_name(&self) -> Option<&str> {
+        let name = self.name.as_deref()?;
+        let name = name.trim_start_matches(['<', '&']);
+        let name = name.strip_prefix("dyn ").unwrap_or(name);
+        let end = name.find("::")?;
+        let krate = &name[..end];
+
+        // Symbols demangled from the v0 mangling scheme carry a disambiguator
+        // such as `alloc[fdfd2bd8633a6659]`.
+        let krate = match krate.find('[') {
+            Some(bracket) => &krate[..bracket],
+            None => krate,
+        };
+
+        if krate.is_empty() || krate.contains(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            None
+        } else {
+            Some(krate)
+        }
+    }
+}
+
+fn has_symbol_prefix(frame: &Frame, prefix: &str) -> bool {
+    match frame.name.as_ref() {
+        Some(name) => name.starts_with(prefix),
+        None => false,
+    }
+}
+
+fn normalize_crate_name(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => (0..=text.len())
+            .filter(|&i| i == 0 || text[i - 1] == b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'?', rest @ ..] => !text.is_empty() && text[0] != b'/' && glob_match(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+/// Environment variable listing additional symbol prefixes of frames to hide
+pub(crate) const HIDE_ENV: &str = "NOCOLOR_EYRE_HIDE";
+/// Environment variable listing symbol prefixes of frames to always show
+pub(crate) const SHOW_ENV: &str = "NOCOLOR_EYRE_SHOW";
+
+/// Apply the comma separated symbol prefixes of `NOCOLOR_EYRE_HIDE` and
+/// `NOCOLOR_EYRE_SHOW` on top of the already filtered `filtered` frames
+///
+/// Frames matching a `NOCOLOR_EYRE_SHOW` prefix are shown even if they also
+/// match a `NOCOLOR_EYRE_HIDE` prefix.
+pub(crate) fn apply_env_overrides<'f>(frames: &'f [Frame], filtered: &mut Vec<&'f Frame>) {
+    for prefix in env_prefixes(HIDE_ENV) {
+        filtered.retain(|frame| !has_symbol_prefix(frame, &prefix));
+    }
+
+    let show = env_prefixes(SHOW_ENV);
+    for frame in frames {
+        if show.iter().any(|prefix| has_symbol_prefix(frame, prefix))
+            && !filtered.iter().any(|f| f.n == frame.n)
+        {
+            filtered.push(frame);
+        }
+    }
+}
+
+fn env_prefixes(var: &str) -> Vec<String> {
+    std::env::var(var)
+        .map(|val| {
+            val.split(',')
+                .map(str::trim)
+                .filter(|prefix| !prefix.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -1,13 +1,17 @@
 //! Module for new types that isolate complext formatting
-use std::fmt;
+use crate::paths::PathRewriter;
+use std::{fmt, path::Path, thread::ThreadId};
 
-pub(crate) struct LocationSection<'a>(pub(crate) Option<&'a std::panic::Location<'a>>);
+pub(crate) struct LocationSection<'a>(
+    pub(crate) Option<&'a std::panic::Location<'a>>,
+    pub(crate) &'a PathRewriter,
+);
 
 impl fmt::Display for LocationSection<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // If known, print panic location.
         if let Some(loc) = self.0 {
-            write!(f, "{}", loc.file())?;
+            write!(f, "{}", self.1.rewrite(Path::new(loc.file())))?;
             write!(f, ":")?;
             write!(f, "{}", loc.line())?;
         } else {
@@ -17,3 +21,29 @@ impl fmt::Display for LocationSection<'_> {
         Ok(())
     }
 }
+
+/// The name and ID of a thread
+#[derive(Debug, Clone)]
+pub(crate) struct ThreadSection {
+    pub(crate) name: Option<String>,
+    pub(crate) id: ThreadId,
+}
+
+impl ThreadSection {
+    pub(crate) fn current() -> Self {
+        let thread = std::thread::current();
+
+        Self {
+            name: thread.name().map(str::to_owned),
+            id: thread.id(),
+        }
+    }
+}
+
+impl fmt::Display for ThreadSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Unnamed threads are called the same as in the default panic message.
+        let name = self.name.as_deref().unwrap_or("<unnamed>");
+        write!(f, "{} ({:?})", name, self.id)
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
-    config::BacktraceFormatter,
+    capture::LazyBacktrace,
+    config::{BacktraceFormatter, Charset, ReportFormat},
     section::help::HelpInfo,
-    writers::{EnvSection, WriterExt},
+    writers::{EnvSection, Guarded, WriterExt},
     Handler,
 };
 use backtrace::Backtrace;
@@ -10,25 +11,107 @@ use std::fmt::Write;
 
 impl std::fmt::Debug for Handler {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("redacted")
+        let mut f = f.debug_struct("Handler");
+        f.field("sections", &self.sections)
+            .field("backtrace_captured", &self.backtrace.is_some())
+            .field("backtrace_rate_limited", &self.backtrace_rate_limited)
+            .field("suppress_backtrace", &self.suppress_backtrace)
+            .field("display_env_section", &self.display_env_section)
+            .field("thread", &self.thread)
+            .field("panic_location", &self.panic_location);
+
+        #[cfg(feature = "track-caller")]
+        f.field("location", &self.location)
+            .field("display_location_section", &self.display_location_section);
+
+        f.finish_non_exhaustive()
     }
 }
 
 impl Handler {
+    /// The location shown in the location section, if it is displayed
+    ///
+    /// Reports of caught panics point at the panic rather than at the
+    /// `catch_panic` call.
+    pub(crate) fn location_section(&self) -> Option<String> {
+        #[cfg(feature = "track-caller")]
+        if !self.display_location_section {
+            return None;
+        }
+
+        match self.panic_location.as_ref() {
+            Some(location) => {
+                let file = self.paths.rewrite(std::path::Path::new(&location.file));
+                Some(format!("{}:{}", file, location.line))
+            }
+            #[cfg(feature = "track-caller")]
+            None => Some(crate::fmt::LocationSection(self.location, &self.paths).to_string()),
+            #[cfg(not(feature = "track-caller"))]
+            None => None,
+        }
+    }
+
     /// Return a reference to the captured `Backtrace` type
+    ///
+    /// Backtraces are captured without resolving their symbols, which happens
+    /// the first time they are formatted or returned from this function.
     pub fn backtrace(&self) -> Option<&Backtrace> {
-        self.backtrace.as_ref()
+        self.backtrace.as_ref().map(LazyBacktrace::resolved)
+    }
+
+    /// Return a reference to the captured `SpanTrace` type
+    #[cfg(feature = "tracing-error")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tracing-error")))]
+    pub fn span_trace(&self) -> Option<&tracing_error::SpanTrace> {
+        self.span_trace.as_ref()
+    }
+
+    /// The issue url section for `error`, if issue reporting is configured
+    /// and not filtered out for this error
+    #[cfg(feature = "issue-url")]
+    pub(crate) fn issue_section<'a>(
+        &'a self,
+        error: &'a (dyn std::error::Error + 'static),
+    ) -> Option<crate::section::github::IssueSection<'a>> {
+        let url = self
+            .issue_url
+            .as_ref()
+            .filter(|_| (*self.issue_filter)(crate::ErrorKind::Recoverable(error)))?;
+
+        let mut payload = String::from("Error: ");
+        for (n, error) in eyre::Chain::new(error).enumerate() {
+            writeln!(&mut payload).ok()?;
+            write!(indented(&mut payload).ind(n), "{}", error).ok()?;
+        }
+
+        let issue_section = crate::section::github::IssueSection::new(url, payload)
+            .with_backtrace(self.backtrace.as_ref().map(LazyBacktrace::resolved))
+            .with_metadata(&self.issue_metadata);
+
+        Some(issue_section)
     }
 
     pub(crate) fn format_backtrace<'a>(
         &'a self,
-        trace: &'a backtrace::Backtrace,
+        trace: &'a LazyBacktrace,
     ) -> BacktraceFormatter<'a> {
         BacktraceFormatter {
             filters: &self.filters,
-            inner: trace,
+            collapse_recursion: self.collapse_recursion,
+            display_raw_addresses: self.display_raw_addresses,
+            charset: Charset::new(self.ascii),
+            paths: &self.paths,
+            inner: trace.resolved(),
         }
     }
+
+    #[cfg(feature = "tracing-error")]
+    pub(crate) fn format_span_trace<'a>(
+        &'a self,
+        span_trace: &'a tracing_error::SpanTrace,
+    ) -> crate::spantrace::SpanTraceFormatter<'a> {
+        crate::spantrace::SpanTraceFormatter(span_trace, &self.paths, Charset::new(self.ascii))
+    }
 }
 
 impl eyre::EyreHandler for Handler {
@@ -41,6 +124,14 @@ impl eyre::EyreHandler for Handler {
             return core::fmt::Debug::fmt(error, f);
         }
 
+        match self.format {
+            ReportFormat::Text => {}
+            #[cfg(feature = "json")]
+            ReportFormat::Json => return write!(f, "{}", crate::json::report(self, error)),
+            ReportFormat::Markdown => return f.write_str(&crate::markdown::report(self, error)?),
+            ReportFormat::Html => return f.write_str(&crate::html::report(self, error)?),
+        }
+
         let errors = || eyre::Chain::new(error).enumerate();
 
         for (n, error) in errors() {
@@ -50,12 +141,19 @@ impl eyre::EyreHandler for Handler {
 
         let mut separated = f.header("\n\n");
 
-        #[cfg(feature = "track-caller")]
-        if self.display_location_section {
+        if let Some(location) = self.location_section() {
             write!(
                 separated.ready(),
                 "{}",
-                crate::SectionExt::header(crate::fmt::LocationSection(self.location), "Location:")
+                crate::SectionExt::header(location, "Location:")
+            )?;
+        }
+
+        if let Some(thread) = self.thread.as_ref() {
+            write!(
+                separated.ready(),
+                "{}",
+                crate::SectionExt::header(thread.to_string(), "Thread:")
             )?;
         }
 
@@ -64,7 +162,11 @@ impl eyre::EyreHandler for Handler {
             .iter()
             .filter(|s| matches!(s, HelpInfo::Error(_)))
         {
-            write!(separated.ready(), "{}", section)?;
+            write!(
+                separated.ready(),
+                "{}",
+                Guarded::new(|f| write!(f, "{}", section))
+            )?;
         }
 
         for section in self
@@ -72,7 +174,22 @@ impl eyre::EyreHandler for Handler {
             .iter()
             .filter(|s| matches!(s, HelpInfo::Custom(_)))
         {
-            write!(separated.ready(), "{}", section)?;
+            write!(
+                separated.ready(),
+                "{}",
+                Guarded::new(|f| write!(f, "{}", section))
+            )?;
+        }
+
+        #[cfg(feature = "tracing-error")]
+        if let Some(span_trace) =
+            crate::spantrace::get_deepest_spantrace(error).or(self.span_trace.as_ref())
+        {
+            write!(
+                indented(&mut separated.ready()).with_format(Format::Uniform { indentation: "  " }),
+                "{}",
+                self.format_span_trace(span_trace)
+            )?;
         }
 
         if !self.suppress_backtrace {
@@ -97,31 +214,21 @@ impl eyre::EyreHandler for Handler {
             .iter()
             .filter(|s| !matches!(s, HelpInfo::Custom(_) | HelpInfo::Error(_)))
         {
-            write!(&mut f, "{}", section)?;
+            write!(&mut f, "{}", Guarded::new(|f| write!(f, "{}", section)))?;
             f = h.ready();
         }
 
         if self.display_env_section {
             let env_section = EnvSection {
                 bt_captured: &self.backtrace.is_some(),
+                bt_rate_limited: self.backtrace_rate_limited,
             };
 
             write!(&mut separated.ready(), "{}", env_section)?;
         }
 
         #[cfg(feature = "issue-url")]
-        if self.issue_url.is_some() && (*self.issue_filter)(crate::ErrorKind::Recoverable(error)) {
-            let url = self.issue_url.as_ref().unwrap();
-            let mut payload = String::from("Error: ");
-            for (n, error) in errors() {
-                writeln!(&mut payload)?;
-                write!(indented(&mut payload).ind(n), "{}", error)?;
-            }
-
-            let issue_section = crate::section::github::IssueSection::new(url, &payload)
-                .with_backtrace(self.backtrace.as_ref())
-                .with_metadata(&**self.issue_metadata);
-
+        if let Some(issue_section) = self.issue_section(error) {
             write!(&mut separated.ready(), "{}", issue_section)?;
         }
 
@@ -0,0 +1,389 @@
+//! Standalone HTML rendering of error and panic reports
+//!
+//! The documents only use inline CSS and `<details>` elements for the
+//! collapsible parts, so they display without external assets or JavaScript.
+use crate::{
+    config::{
+        lib_verbosity, panic_verbosity, BacktraceFormatter, Frame, PanicReport, SourceSection,
+        Verbosity,
+    },
+    section::help::HelpInfo,
+    writers::{EnvSection, Guarded},
+    Handler,
+};
+use std::fmt::{self, Write};
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+h1 { font-size: 1.5em; }
+h2 { font-size: 1.2em; margin-top: 1.5em; }
+pre { background: #f6f8fa; padding: 0.5em; overflow-x: auto; }
+ol.chain li, ul li { white-space: pre-wrap; }
+summary { cursor: pointer; font-weight: bold; }
+details.hidden > summary { font-weight: normal; color: #6a737d; }
+div.frame { margin: 0.5em 0; }
+div.frame pre { margin: 0.25em 0 0 2em; }";
+
+/// Writer escaping everything written through it, for use in HTML text and
+/// attribute values
+struct Escaped<'a>(&'a mut String);
+
+impl Write for Escaped<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c {
+                '&' => self.0.push_str("&amp;"),
+                '<' => self.0.push_str("&lt;"),
+                '>' => self.0.push_str("&gt;"),
+                '"' => self.0.push_str("&quot;"),
+                '\'' => self.0.push_str("&#39;"),
+                c => self.0.push(c),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A standalone HTML document made of sections with a `<h2>` heading each
+struct Document {
+    body: String,
+}
+
+impl Document {
+    fn new(title: &str) -> Self {
+        let mut body = String::from("<h1>");
+        body.push_str(title);
+        body.push_str("</h1>\n");
+
+        Document { body }
+    }
+
+    /// Append `html` as is
+    fn raw(&mut self, html: &str) {
+        self.body.push_str(html);
+    }
+
+    /// Append `text`, escaped
+    fn text(&mut self, text: impl fmt::Display) -> fmt::Result {
+        write!(Escaped(&mut self.body), "{}", text)
+    }
+
+    fn heading(&mut self, heading: &str) {
+        self.raw("<h2>");
+        self.raw(heading);
+        self.raw("</h2>\n");
+    }
+
+    /// A section with inline code, such as a location
+    fn push_code(&mut self, heading: &str, code: impl fmt::Display) -> fmt::Result {
+        self.heading(heading);
+        self.raw("<p><code>");
+        self.text(code)?;
+        self.raw("</code></p>\n");
+        Ok(())
+    }
+
+    /// A section with preformatted text
+    fn push_pre(&mut self, heading: &str, text: impl fmt::Display) -> fmt::Result {
+        self.heading(heading);
+        self.raw("<pre>");
+        self.text(text)?;
+        self.raw("</pre>\n");
+        Ok(())
+    }
+
+    /// A section with preformatted text, collapsed until clicked on
+    #[cfg(feature = "tracing-error")]
+    fn push_collapsed_pre(&mut self, summary: &str, text: impl fmt::Display) -> fmt::Result {
+        self.raw("<details>\n<summary>");
+        self.raw(summary);
+        self.raw("</summary>\n<pre>");
+        self.text(text)?;
+        self.raw("</pre>\n</details>\n");
+        Ok(())
+    }
+
+    /// A section with a bullet list, items are `(label, text)` pairs
+    fn push_list(&mut self, heading: &str, items: &[(Option<&str>, String)]) {
+        if items.is_empty() {
+            return;
+        }
+
+        self.heading(heading);
+        self.raw("<ul>\n");
+        for (label, item) in items {
+            self.raw("<li>");
+            if let Some(label) = label {
+                self.raw("<strong>");
+                self.raw(label);
+                self.raw(":</strong> ");
+            }
+            // Writing to a `String` only fails if `item` does.
+            let _ = self.text(item);
+            self.raw("</li>\n");
+        }
+        self.raw("</ul>\n");
+    }
+
+    /// A section with a link
+    #[cfg(feature = "issue-url")]
+    fn push_link(&mut self, heading: &str, url: &str, text: &str) -> fmt::Result {
+        self.heading(heading);
+        self.raw("<p><a href=\"");
+        self.text(url)?;
+        self.raw("\">");
+        self.raw(text);
+        self.raw("</a></p>\n");
+        Ok(())
+    }
+
+    /// The backtrace, collapsed until clicked on, with the frames removed by
+    /// the frame filters in their own nested collapsed blocks
+    ///
+    /// Source snippets are only included at `Verbosity::Full`, like in text
+    /// reports.
+    fn push_backtrace(
+        &mut self,
+        formatter: BacktraceFormatter<'_>,
+        verbosity: Verbosity,
+    ) -> fmt::Result {
+        let source = verbosity >= Verbosity::Full;
+        let frames = formatter.frames();
+        let filtered = formatter.filter(&frames);
+
+        let mut shown = vec![false; frames.len()];
+        for frame in &filtered {
+            shown[frame.n - 1] = true;
+        }
+
+        self.raw("<details>\n<summary>Backtrace</summary>\n");
+
+        let mut hidden = Vec::new();
+        for frame in &frames {
+            if shown[frame.n - 1] {
+                self.push_hidden_frames(&mut hidden, &formatter)?;
+                self.push_frame(frame, &formatter, source)?;
+            } else {
+                hidden.push(frame);
+            }
+        }
+        self.push_hidden_frames(&mut hidden, &formatter)?;
+
+        self.raw("</details>\n");
+        Ok(())
+    }
+
+    fn push_hidden_frames(
+        &mut self,
+        hidden: &mut Vec<&Frame>,
+        formatter: &BacktraceFormatter<'_>,
+    ) -> fmt::Result {
+        if hidden.is_empty() {
+            return Ok(());
+        }
+
+        // Count physical frames, not the symbols inlined into them.
+        let mut indices: Vec<_> = hidden.iter().map(|frame| frame.index).collect();
+        indices.dedup();
+        let n = indices.len();
+
+        write!(
+            &mut self.body,
+            "<details class=\"hidden\">\n<summary>{} frame{} hidden</summary>\n",
+            n,
+            if n == 1 { "" } else { "s" }
+        )?;
+        for frame in hidden.drain(..) {
+            self.push_frame(frame, formatter, false)?;
+        }
+        self.raw("</details>\n");
+
+        Ok(())
+    }
+
+    /// A single frame, with a snippet of its source if `source` is set and
+    /// the source is available
+    fn push_frame(
+        &mut self,
+        frame: &Frame,
+        formatter: &BacktraceFormatter<'_>,
+        source: bool,
+    ) -> fmt::Result {
+        write!(
+            &mut self.body,
+            "<div class=\"frame\"><code>{:>2}: ",
+            frame.index
+        )?;
+        self.text(frame.name.as_deref().unwrap_or("<unknown>"))?;
+        self.raw("</code>");
+        if frame.inlined {
+            self.raw(" <em>[inlined]</em>");
+        }
+
+        self.raw("<br>\nat <code>");
+        match frame.filename.as_ref() {
+            Some(path) => self.text(formatter.paths.rewrite(path))?,
+            None => self.text("<unknown source file>")?,
+        }
+        match frame.lineno {
+            Some(lineno) => write!(&mut self.body, ":{}", lineno)?,
+            None => self.text(":<unknown line>")?,
+        }
+        if let Some(colno) = frame.colno {
+            write!(&mut self.body, ":{}", colno)?;
+        }
+        self.raw("</code>\n");
+
+        if source {
+            let mut snippet = String::new();
+            write!(
+                &mut snippet,
+                "{}",
+                SourceSection(frame, formatter.paths, formatter.charset)
+            )?;
+            if !snippet.is_empty() {
+                self.raw("<pre>");
+                self.text(snippet)?;
+                self.raw("</pre>\n");
+            }
+        }
+
+        self.raw("</div>\n");
+        Ok(())
+    }
+
+    fn push_env_section(&mut self, env_section: &EnvSection<'_>) {
+        let hints: Vec<_> = env_section
+            .hints()
+            .into_iter()
+            .map(|hint| (None, hint))
+            .collect();
+        self.push_list("Environment", &hints);
+    }
+
+    fn finish(self, title: &str) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            title, STYLE, self.body
+        )
+    }
+}
+
+/// Render the report held by `handler` for `error` as a standalone HTML
+/// document
+pub(crate) fn report(
+    handler: &Handler,
+    error: &(dyn std::error::Error + 'static),
+) -> Result<String, fmt::Error> {
+    let mut doc = Document::new("Error report");
+
+    doc.heading("Error");
+    doc.raw("<ol class=\"chain\" start=\"0\">\n");
+    for error in eyre::Chain::new(error) {
+        doc.raw("<li>");
+        doc.text(error)?;
+        doc.raw("</li>\n");
+    }
+    doc.raw("</ol>\n");
+
+    if let Some(location) = handler.location_section() {
+        doc.push_code("Location", location)?;
+    }
+
+    if let Some(thread) = handler.thread.as_ref() {
+        doc.push_code("Thread", thread)?;
+    }
+
+    let mut help = Vec::new();
+    for section in &handler.sections {
+        let rendered = Guarded::new(|f| match section {
+            HelpInfo::Error(_) | HelpInfo::Custom(_) => write!(f, "{}", section),
+            HelpInfo::Note(note) => write!(f, "{}", note),
+            HelpInfo::Warning(warning) => write!(f, "{}", warning),
+            HelpInfo::Suggestion(suggestion) => write!(f, "{}", suggestion),
+        });
+
+        match section {
+            HelpInfo::Error(_) => doc.push_pre("Related error", rendered)?,
+            HelpInfo::Custom(_) => doc.push_pre("Section", rendered)?,
+            HelpInfo::Note(_) => help.push((Some("Note"), rendered.to_string())),
+            HelpInfo::Warning(_) => help.push((Some("Warning"), rendered.to_string())),
+            HelpInfo::Suggestion(_) => help.push((Some("Suggestion"), rendered.to_string())),
+        }
+    }
+
+    #[cfg(feature = "tracing-error")]
+    if let Some(span_trace) =
+        crate::spantrace::get_deepest_spantrace(error).or(handler.span_trace.as_ref())
+    {
+        let span_trace = handler.format_span_trace(span_trace);
+        doc.push_collapsed_pre("Span trace", span_trace)?;
+    }
+
+    if !handler.suppress_backtrace {
+        if let Some(backtrace) = handler.backtrace.as_ref() {
+            doc.push_backtrace(handler.format_backtrace(backtrace), lib_verbosity())?;
+        }
+    }
+
+    doc.push_list("Help", &help);
+
+    if handler.display_env_section {
+        doc.push_env_section(&EnvSection {
+            bt_captured: &handler.backtrace.is_some(),
+            bt_rate_limited: handler.backtrace_rate_limited,
+        });
+    }
+
+    #[cfg(feature = "issue-url")]
+    if let Some(issue_section) = handler.issue_section(error) {
+        doc.push_link("Report", &issue_section.url(), "Report this error")?;
+    }
+
+    Ok(doc.finish("Error report"))
+}
+
+/// Render a panic report as a standalone HTML document
+pub(crate) fn panic_report(report: &PanicReport<'_>) -> Result<String, fmt::Error> {
+    let hook = report.hook;
+    let mut doc = Document::new("Panic report");
+
+    doc.push_pre("Panic", report.payload_message())?;
+
+    if let Some(location) = report.location_section() {
+        doc.push_code("Location", location)?;
+    }
+    if let Some(thread) = report.thread_section() {
+        doc.push_code("Thread", thread)?;
+    }
+
+    if let Some(section) = hook.section.as_ref() {
+        doc.push_pre("Section", Guarded::new(|f| write!(f, "{}", section)))?;
+    }
+
+    #[cfg(feature = "tracing-error")]
+    if let Some(span_trace) = report.span_trace.as_ref() {
+        let span_trace = hook.format_span_trace(span_trace);
+        doc.push_collapsed_pre("Span trace", span_trace)?;
+    }
+
+    if let Some(backtrace) = report.backtrace.as_ref() {
+        doc.push_backtrace(hook.format_backtrace(backtrace), panic_verbosity())?;
+    }
+
+    if hook.display_env_section {
+        doc.push_env_section(&EnvSection {
+            bt_captured: &report.backtrace.is_some(),
+            bt_rate_limited: false,
+        });
+    }
+
+    #[cfg(feature = "issue-url")]
+    if let Some(issue_section) = report.issue_section() {
+        doc.push_link("Report", &issue_section.url(), "Report this panic")?;
+    }
+
+    Ok(doc.finish("Panic report"))
+}
@@ -0,0 +1,219 @@
+//! Machine readable JSON rendering of error and panic reports
+use crate::{
+    config::{BacktraceFormatter, Frame, PanicReport},
+    fmt::ThreadSection,
+    paths::PathRewriter,
+    section::help::HelpInfo,
+    writers::{EnvSection, Guarded},
+    Handler,
+};
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Render the report held by `handler` for `error` as a JSON object
+pub(crate) fn report(handler: &Handler, error: &(dyn std::error::Error + 'static)) -> Value {
+    #[cfg(feature = "track-caller")]
+    let display_location = handler.display_location_section;
+    #[cfg(not(feature = "track-caller"))]
+    let display_location = true;
+
+    // Reports of caught panics point at the panic rather than at the
+    // `catch_panic` call.
+    let location = match handler.panic_location.as_ref() {
+        _ if !display_location => Value::Null,
+        Some(panic) => json!({
+            "file": handler.paths.rewrite(Path::new(&panic.file)),
+            "line": panic.line,
+            "column": panic.column,
+        }),
+        #[cfg(feature = "track-caller")]
+        None => location(handler.location, &handler.paths),
+        #[cfg(not(feature = "track-caller"))]
+        None => Value::Null,
+    };
+
+    let backtrace = match handler.backtrace.as_ref() {
+        Some(bt) if !handler.suppress_backtrace => backtrace(handler.format_backtrace(bt)),
+        _ => Value::Null,
+    };
+
+    let env = if handler.display_env_section {
+        EnvSection {
+            bt_captured: &handler.backtrace.is_some(),
+            bt_rate_limited: handler.backtrace_rate_limited,
+        }
+        .hints()
+    } else {
+        Vec::new()
+    };
+
+    let report = json!({
+        "errors": chain(error),
+        "location": location,
+        "thread": handler.thread.as_ref().map(|thread| thread.name.as_deref()),
+        "thread_id": handler.thread.as_ref().map(|thread| format!("{:?}", thread.id)),
+        "sections": handler.sections.iter().map(section).collect::<Vec<_>>(),
+        "backtrace": backtrace,
+        "env": env,
+    });
+
+    #[cfg(feature = "tracing-error")]
+    let report = with_span_trace(
+        report,
+        crate::spantrace::get_deepest_spantrace(error).or(handler.span_trace.as_ref()),
+        &handler.paths,
+    );
+
+    report
+}
+
+fn chain(error: &(dyn std::error::Error + 'static)) -> Vec<String> {
+    eyre::Chain::new(error)
+        .map(|error| error.to_string())
+        .collect()
+}
+
+fn location(location: Option<&std::panic::Location<'_>>, paths: &PathRewriter) -> Value {
+    match location {
+        Some(location) => json!({
+            "file": paths.rewrite(Path::new(location.file())),
+            "line": location.line(),
+            "column": location.column(),
+        }),
+        None => Value::Null,
+    }
+}
+
+fn backtrace(formatter: BacktraceFormatter<'_>) -> Value {
+    let frames = formatter.frames();
+    let filtered = formatter.filter(&frames).into_iter();
+    Value::Array(
+        filtered
+            .map(|shown| frame(shown, formatter.paths))
+            .collect(),
+    )
+}
+
+fn frame(frame: &Frame, paths: &PathRewriter) -> Value {
+    json!({
+        "n": frame.n,
+        "index": frame.index,
+        "inlined": frame.inlined,
+        "name": frame.name,
+        "lineno": frame.lineno,
+        "colno": frame.colno,
+        "filename": frame.filename.as_deref().map(|path| paths.rewrite(path)),
+        "ip": frame.ip,
+        "module_base": frame.module_base,
+    })
+}
+
+#[cfg(feature = "tracing-error")]
+fn span_trace(span_trace: &tracing_error::SpanTrace, paths: &PathRewriter) -> Value {
+    let mut spans = Vec::new();
+
+    span_trace.with_spans(|metadata, fields| {
+        spans.push(json!({
+            "n": spans.len(),
+            "target": metadata.target(),
+            "name": metadata.name(),
+            "fields": fields,
+            "lineno": metadata.line(),
+            "filename": metadata.file().map(|file| paths.rewrite(Path::new(file))),
+        }));
+        true
+    });
+
+    Value::Array(spans)
+}
+
+/// Add the `spantrace` field to a report
+#[cfg(feature = "tracing-error")]
+fn with_span_trace(
+    mut report: Value,
+    trace: Option<&tracing_error::SpanTrace>,
+    paths: &PathRewriter,
+) -> Value {
+    report["spantrace"] = trace.map_or(Value::Null, |trace| span_trace(trace, paths));
+    report
+}
+
+fn section(section: &HelpInfo) -> Value {
+    match section {
+        HelpInfo::Error(error) => json!({
+            "kind": "error",
+            "errors": chain(error.as_ref()),
+        }),
+        HelpInfo::Custom(section) => json!({
+            "kind": "custom",
+            "message": render(section),
+        }),
+        HelpInfo::Note(note) => json!({
+            "kind": "note",
+            "message": render(note),
+        }),
+        HelpInfo::Warning(warning) => json!({
+            "kind": "warning",
+            "message": render(warning),
+        }),
+        HelpInfo::Suggestion(suggestion) => json!({
+            "kind": "suggestion",
+            "message": render(suggestion),
+        }),
+    }
+}
+
+/// Render a user supplied section, see `Guarded`
+fn render(section: &impl std::fmt::Display) -> String {
+    Guarded::new(|f| write!(f, "{}", section)).to_string()
+}
+
+/// Render a panic report as a JSON object
+pub(crate) fn panic_report(report: &PanicReport<'_>) -> Value {
+    let payload = report.payload_message();
+
+    let backtrace = match report.backtrace.as_ref() {
+        Some(bt) => backtrace(report.hook.format_backtrace(bt)),
+        None => Value::Null,
+    };
+
+    let env = if report.hook.display_env_section {
+        EnvSection {
+            bt_captured: &report.backtrace.is_some(),
+            bt_rate_limited: false,
+        }
+        .hints()
+    } else {
+        Vec::new()
+    };
+
+    let location = match report.location_section() {
+        Some(section) => location(section.0, &report.hook.paths),
+        None => Value::Null,
+    };
+
+    // The thread is always part of the machine readable report, regardless
+    // of `HookBuilder::display_thread_section`.
+    let thread = ThreadSection::current();
+
+    #[cfg(feature = "issue-url")]
+    let issue_url = report.issue_section().map(|section| section.url());
+    #[cfg(not(feature = "issue-url"))]
+    let issue_url: Option<String> = None;
+
+    let value = json!({
+        "message": payload,
+        "location": location,
+        "thread": thread.name,
+        "thread_id": format!("{:?}", thread.id),
+        "backtrace": backtrace,
+        "section": report.hook.section.as_ref().map(render),
+        "issue_url": issue_url,
+        "env": env,
+    });
+
+    #[cfg(feature = "tracing-error")]
+    let value = with_span_trace(value, report.span_trace.as_ref(), &report.hook.paths);
+
+    value
+}
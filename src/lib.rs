@@ -49,16 +49,20 @@
 //! }
 //! ```
 //!
-//! ### Disabling tracing support
+//! ### Enabling tracing support
 //!
-//! If you don't plan on using `tracing_error` and `SpanTrace` you can disable the
-//! tracing integration to cut down on unused dependencies:
+//! `SpanTrace` capture is provided by the optional `tracing-error` feature. It
+//! records the active `tracing` spans whenever a report is created or a panic
+//! occurs, and renders them in a `SPANTRACE` section next to the backtrace:
 //!
 //! ```toml
 //! [dependencies]
-//! color-eyre = { version = "0.6", default-features = false }
+//! nocolor-eyre = { version = "0.6", features = ["tracing-error"] }
 //! ```
 //!
+//! Span traces are only populated when a `tracing_error::ErrorLayer` is part of
+//! the installed `tracing` subscriber.
+//!
 //! ### Disabling SpanTrace capture by default
 //!
 //! color-eyre defaults to capturing span traces. This is because `SpanTrace`
@@ -319,6 +323,13 @@
 //!
 //! For an example of how to setup custom filters, check out [`examples/custom_filter.rs`].
 //!
+//! The configured filters can be adjusted without recompiling through the
+//! `NOCOLOR_EYRE_HIDE` and `NOCOLOR_EYRE_SHOW` environment variables, which
+//! take comma separated symbol prefixes of frames to hide or show, for
+//! example `NOCOLOR_EYRE_SHOW=tokio::,hyper::`. Shown prefixes take precedence
+//! over hidden ones and over the configured filters. `COLORBT_SHOW_HIDDEN=1`
+//! disables frame filtering altogether.
+//!
 //! [`eyre`]: https://docs.rs/eyre
 //! [`tracing-error`]: https://docs.rs/tracing-error
 //! [`color-backtrace`]: https://docs.rs/color-backtrace
@@ -359,7 +370,6 @@
 
 use std::sync::Arc;
 
-use backtrace::Backtrace;
 pub use eyre;
 #[doc(hidden)]
 pub use eyre::Report;
@@ -369,14 +379,33 @@ use section::help::HelpInfo;
 #[doc(hidden)]
 pub use section::Section as Help;
 pub use section::{IndentedSection, Section, SectionExt};
+pub use view::{ReportLocation, ReportSection, ReportView, Sections};
 #[doc(hidden)]
 pub use Handler as Context;
 
+pub use catch::{catch_panic, PanicError};
+
+mod capture;
+mod catch;
 pub mod config;
+mod crash;
+mod exit;
+mod filter;
 mod fmt;
 mod handler;
+mod html;
+#[cfg(feature = "json")]
+mod json;
+mod markdown;
+mod modules;
+mod paths;
+mod payload;
 pub(crate) mod private;
 pub mod section;
+mod sources;
+#[cfg(feature = "tracing-error")]
+mod spantrace;
+mod view;
 mod writers;
 
 /// A custom handler type for [`eyre::Report`] which provides colorful error
@@ -393,12 +422,23 @@ mod writers;
 /// [`nocolor_eyre::Result`]: type.Result.html
 pub struct Handler {
     filters: Arc<[Box<config::FilterCallback>]>,
-    backtrace: Option<Backtrace>,
+    collapse_recursion: bool,
+    display_raw_addresses: bool,
+    ascii: bool,
+    paths: Arc<paths::PathRewriter>,
+    format: config::ReportFormat,
+    backtrace: Option<capture::LazyBacktrace>,
+    backtrace_rate_limited: bool,
+    #[cfg(feature = "tracing-error")]
+    span_trace: Option<tracing_error::SpanTrace>,
     suppress_backtrace: bool,
     sections: Vec<HelpInfo>,
     display_env_section: bool,
     #[cfg(feature = "track-caller")]
     display_location_section: bool,
+    thread: Option<fmt::ThreadSection>,
+    /// The location of the panic this report was created from by `catch_panic`
+    panic_location: Option<catch::PanicLocation>,
     #[cfg(feature = "issue-url")]
     issue_url: Option<String>,
     #[cfg(feature = "issue-url")]
@@ -414,8 +454,10 @@ pub struct Handler {
 #[cfg(feature = "issue-url")]
 #[cfg_attr(docsrs, doc(cfg(feature = "issue-url")))]
 pub enum ErrorKind<'a> {
-    /// A non recoverable error aka `panic!`
-    NonRecoverable(&'a dyn std::any::Any),
+    /// A non recoverable error aka `panic!`, with its payload and its message
+    /// as rendered by the formatters registered via
+    /// `HookBuilder::panic_payload_formatter`
+    NonRecoverable(&'a dyn std::any::Any, &'a str),
     /// A recoverable error aka `impl std::error::Error`
     Recoverable(&'a (dyn std::error::Error + 'static)),
 }
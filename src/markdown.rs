@@ -0,0 +1,264 @@
+//! Markdown rendering of error and panic reports, also used for the body of
+//! issue urls
+use crate::{
+    config::PanicReport,
+    section::help::HelpInfo,
+    writers::{DisplayExt, EnvSection, Guarded},
+    Handler,
+};
+use indenter::indented;
+use std::fmt::{self, Write};
+
+/// A Markdown document made of sections with a `##` heading each
+pub(crate) struct Body {
+    body: String,
+}
+
+impl Body {
+    pub(crate) fn new() -> Self {
+        Body {
+            body: String::new(),
+        }
+    }
+
+    pub(crate) fn push_section<T>(&mut self, header: &'static str, section: T) -> fmt::Result
+    where
+        T: fmt::Display,
+    {
+        let separator = if self.body.is_empty() { "" } else { "\n\n" };
+        let header = header
+            .with_header("## ")
+            .with_header(separator)
+            .with_footer("\n");
+
+        write!(&mut self.body, "{}", section.with_header(header))
+    }
+
+    #[cfg(feature = "issue-url")]
+    pub(crate) fn as_str(&self) -> &str {
+        &self.body
+    }
+}
+
+/// A fenced code block
+pub(crate) struct ConsoleSection<T>(pub(crate) T);
+
+impl<T> fmt::Display for ConsoleSection<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (&self.0).with_header("```\n").with_footer("\n```").fmt(f)
+    }
+}
+
+/// A block that is collapsed until clicked on
+#[cfg(feature = "issue-url")]
+pub(crate) struct Collapsed<T>(pub(crate) T);
+
+#[cfg(feature = "issue-url")]
+impl<T> fmt::Display for Collapsed<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (&self.0)
+            .with_header("\n<details>\n\n")
+            .with_footer("\n</details>")
+            .fmt(f)
+    }
+}
+
+#[cfg(feature = "issue-url")]
+pub(crate) struct DisplayFromDebug<T>(pub(crate) T);
+
+#[cfg(feature = "issue-url")]
+impl<T> fmt::Display for DisplayFromDebug<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Inline code
+struct Code<T>(T);
+
+impl<T> fmt::Display for Code<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`", self.0)
+    }
+}
+
+/// A bullet list with an optional bold label per item, continuation lines of
+/// an item are indented to stay part of it
+#[derive(Default)]
+struct List(String);
+
+impl List {
+    fn push(&mut self, label: Option<&str>, item: impl fmt::Display) -> fmt::Result {
+        if !self.0.is_empty() {
+            self.0.push('\n');
+        }
+
+        self.0.push_str("- ");
+        if let Some(label) = label {
+            write!(&mut self.0, "**{}:** ", label)?;
+        }
+
+        let mut rendered = String::new();
+        write!(&mut rendered, "{}", item)?;
+        for (i, line) in rendered.lines().enumerate() {
+            if i > 0 {
+                self.0.push_str("\n  ");
+            }
+            self.0.push_str(line);
+        }
+
+        Ok(())
+    }
+}
+
+/// Render the report held by `handler` for `error` as Markdown
+pub(crate) fn report(
+    handler: &Handler,
+    error: &(dyn std::error::Error + 'static),
+) -> Result<String, fmt::Error> {
+    let mut body = Body::new();
+
+    let mut errors = String::new();
+    for (n, error) in eyre::Chain::new(error).enumerate() {
+        if n > 0 {
+            writeln!(&mut errors)?;
+        }
+        write!(indented(&mut errors).ind(n), "{}", error)?;
+    }
+    body.push_section("Error", ConsoleSection(errors))?;
+
+    if let Some(location) = handler.location_section() {
+        body.push_section("Location", Code(location))?;
+    }
+
+    if let Some(thread) = handler.thread.as_ref() {
+        body.push_section("Thread", Code(thread))?;
+    }
+
+    let mut help = List::default();
+    for section in &handler.sections {
+        let rendered = Guarded::new(|f| match section {
+            HelpInfo::Error(_) | HelpInfo::Custom(_) => write!(f, "{}", section),
+            HelpInfo::Note(note) => write!(f, "{}", note),
+            HelpInfo::Warning(warning) => write!(f, "{}", warning),
+            HelpInfo::Suggestion(suggestion) => write!(f, "{}", suggestion),
+        });
+
+        match section {
+            HelpInfo::Error(_) => body.push_section("Related error", ConsoleSection(rendered))?,
+            HelpInfo::Custom(_) => body.push_section("Section", ConsoleSection(rendered))?,
+            HelpInfo::Note(_) => help.push(Some("Note"), rendered)?,
+            HelpInfo::Warning(_) => help.push(Some("Warning"), rendered)?,
+            HelpInfo::Suggestion(_) => help.push(Some("Suggestion"), rendered)?,
+        }
+    }
+
+    #[cfg(feature = "tracing-error")]
+    if let Some(span_trace) =
+        crate::spantrace::get_deepest_spantrace(error).or(handler.span_trace.as_ref())
+    {
+        let span_trace = handler.format_span_trace(span_trace);
+        body.push_section("Span trace", ConsoleSection(span_trace))?;
+    }
+
+    if !handler.suppress_backtrace {
+        if let Some(backtrace) = handler.backtrace.as_ref() {
+            let backtrace = handler.format_backtrace(backtrace);
+            body.push_section("Backtrace", ConsoleSection(backtrace))?;
+        }
+    }
+
+    if !help.0.is_empty() {
+        body.push_section("Help", help.0)?;
+    }
+
+    if handler.display_env_section {
+        let env_section = EnvSection {
+            bt_captured: &handler.backtrace.is_some(),
+            bt_rate_limited: handler.backtrace_rate_limited,
+        };
+        push_env_section(&mut body, &env_section)?;
+    }
+
+    #[cfg(feature = "issue-url")]
+    if let Some(issue_section) = handler.issue_section(error) {
+        let url = issue_section.url();
+        body.push_section("Report", format_args!("[Report this error]({})", url))?;
+    }
+
+    Ok(body.body)
+}
+
+/// Render a panic report as Markdown
+pub(crate) fn panic_report(report: &PanicReport<'_>) -> Result<String, fmt::Error> {
+    let hook = report.hook;
+    let mut body = Body::new();
+
+    body.push_section("Panic", ConsoleSection(report.payload_message()))?;
+
+    if let Some(location) = report.location_section() {
+        body.push_section("Location", Code(location))?;
+    }
+    if let Some(thread) = report.thread_section() {
+        body.push_section("Thread", Code(thread))?;
+    }
+
+    if let Some(section) = hook.section.as_ref() {
+        let section = Guarded::new(|f| write!(f, "{}", section));
+        body.push_section("Section", ConsoleSection(section))?;
+    }
+
+    #[cfg(feature = "tracing-error")]
+    if let Some(span_trace) = report.span_trace.as_ref() {
+        let span_trace = hook.format_span_trace(span_trace);
+        body.push_section("Span trace", ConsoleSection(span_trace))?;
+    }
+
+    if let Some(backtrace) = report.backtrace.as_ref() {
+        body.push_section(
+            "Backtrace",
+            ConsoleSection(hook.format_backtrace(backtrace)),
+        )?;
+    }
+
+    if hook.display_env_section {
+        let env_section = EnvSection {
+            bt_captured: &report.backtrace.is_some(),
+            bt_rate_limited: false,
+        };
+        push_env_section(&mut body, &env_section)?;
+    }
+
+    #[cfg(feature = "issue-url")]
+    if let Some(issue_section) = report.issue_section() {
+        let url = issue_section.url();
+        body.push_section("Report", format_args!("[Report this panic]({})", url))?;
+    }
+
+    Ok(body.body)
+}
+
+fn push_env_section(body: &mut Body, env_section: &EnvSection<'_>) -> fmt::Result {
+    let mut hints = List::default();
+    for hint in env_section.hints() {
+        hints.push(None, hint)?;
+    }
+
+    if hints.0.is_empty() {
+        return Ok(());
+    }
+
+    body.push_section("Environment", hints.0)
+}
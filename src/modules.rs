@@ -0,0 +1,185 @@
+//! Lookup of the modules loaded into the current process, for printing raw
+//! frame addresses that can be symbolized offline
+use std::{fmt, ops::Range, path::PathBuf};
+
+/// A shared object or executable mapped into the current process
+#[derive(Debug)]
+pub(crate) struct Module {
+    pub(crate) path: Option<PathBuf>,
+    /// The load bias, which needs to be subtracted from instruction addresses
+    /// to get addresses in the module's file
+    pub(crate) base: usize,
+    pub(crate) build_id: Option<Vec<u8>>,
+    segments: Vec<Range<usize>>,
+}
+
+/// The modules loaded into the current process
+#[derive(Default)]
+pub(crate) struct Modules(Vec<Module>);
+
+impl Modules {
+    /// Enumerate the currently loaded modules
+    ///
+    /// Only supported on Linux, other platforms get no modules.
+    pub(crate) fn load() -> Self {
+        #[cfg(target_os = "linux")]
+        {
+            Self(linux::modules())
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Self::default()
+        }
+    }
+
+    /// The module mapping the instruction address `ip`
+    pub(crate) fn find(&self, ip: usize) -> Option<&Module> {
+        self.0
+            .iter()
+            .find(|module| module.segments.iter().any(|segment| segment.contains(&ip)))
+    }
+}
+
+/// The raw address line printed for a physical frame
+///
+/// Formatted as `ip=0x... base=0x... build-id=... module=...`, leaving out
+/// unknown values. The module path comes last as it may contain spaces.
+#[derive(Debug)]
+pub(crate) struct RawAddress<'a> {
+    pub(crate) ip: usize,
+    pub(crate) module_base: Option<usize>,
+    pub(crate) module: Option<&'a Module>,
+}
+
+impl fmt::Display for RawAddress<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ip={:#x}", self.ip)?;
+
+        let base = self.module.map(|module| module.base).or(self.module_base);
+        if let Some(base) = base {
+            write!(f, " base={:#x}", base)?;
+        }
+
+        let module = match self.module {
+            Some(module) => module,
+            None => return Ok(()),
+        };
+
+        if let Some(build_id) = module.build_id.as_ref() {
+            write!(f, " build-id=")?;
+            for byte in build_id {
+                write!(f, "{:02x}", byte)?;
+            }
+        }
+
+        if let Some(path) = module.path.as_ref() {
+            write!(f, " module={}", path.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::Module;
+    use std::{ffi::CStr, path::PathBuf};
+
+    const NT_GNU_BUILD_ID: u32 = 3;
+
+    pub(super) fn modules() -> Vec<Module> {
+        let mut modules = Vec::new();
+
+        // SAFETY: the callback only accesses `modules` through `data` while
+        // `dl_iterate_phdr` runs.
+        unsafe {
+            libc::dl_iterate_phdr(
+                Some(callback),
+                &mut modules as *mut Vec<Module> as *mut libc::c_void,
+            );
+        }
+
+        modules
+    }
+
+    unsafe extern "C" fn callback(
+        info: *mut libc::dl_phdr_info,
+        _size: libc::size_t,
+        data: *mut libc::c_void,
+    ) -> libc::c_int {
+        let modules = &mut *(data as *mut Vec<Module>);
+        let info = &*info;
+        let base = info.dlpi_addr as usize;
+
+        let name = if info.dlpi_name.is_null() {
+            &[][..]
+        } else {
+            CStr::from_ptr(info.dlpi_name).to_bytes()
+        };
+
+        // The main executable is reported first, without a name.
+        let path = if !name.is_empty() {
+            Some(PathBuf::from(String::from_utf8_lossy(name).into_owned()))
+        } else if modules.is_empty() {
+            std::env::current_exe().ok()
+        } else {
+            None
+        };
+
+        let headers = if info.dlpi_phdr.is_null() {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(info.dlpi_phdr, info.dlpi_phnum as usize)
+        };
+
+        let mut segments = Vec::new();
+        let mut build_id = None;
+
+        for header in headers {
+            let start = base.wrapping_add(header.p_vaddr as usize);
+            match header.p_type {
+                libc::PT_LOAD => segments.push(start..start + header.p_memsz as usize),
+                libc::PT_NOTE if build_id.is_none() => {
+                    let notes =
+                        std::slice::from_raw_parts(start as *const u8, header.p_memsz as usize);
+                    build_id = find_build_id(notes);
+                }
+                _ => (),
+            }
+        }
+
+        modules.push(Module {
+            path,
+            base,
+            build_id,
+            segments,
+        });
+
+        0
+    }
+
+    fn find_build_id(mut notes: &[u8]) -> Option<Vec<u8>> {
+        let word = |bytes: &[u8]| u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let align = |len: usize| (len + 3) & !3;
+
+        while notes.len() >= 12 {
+            let name_len = word(&notes[0..]) as usize;
+            let desc_len = word(&notes[4..]) as usize;
+            let kind = word(&notes[8..]);
+
+            let desc_start = 12 + align(name_len);
+            let desc_end = desc_start + desc_len;
+            if desc_end > notes.len() {
+                return None;
+            }
+
+            if kind == NT_GNU_BUILD_ID && &notes[12..12 + name_len] == b"GNU\0" {
+                return Some(notes[desc_start..desc_end].to_vec());
+            }
+
+            notes = &notes[align(desc_end).min(notes.len())..];
+        }
+
+        None
+    }
+}
@@ -0,0 +1,169 @@
+//! Rewriting of source paths for display in frames and locations, and for
+//! finding source files on machines other than the build machine
+use crate::sources::EmbeddedSources;
+use std::{
+    borrow::Cow,
+    ffi::OsStr,
+    path::{Component, Path, PathBuf},
+};
+
+/// Rewrites source paths into shorter forms that don't leak details of the
+/// build machine, and into the paths the sources can be read from
+///
+/// For display, user supplied prefix mappings take precedence over the
+/// workspace root, which takes precedence over the built in shortening of
+/// Cargo registry and standard library paths.
+#[derive(Debug)]
+pub(crate) struct PathRewriter {
+    pub(crate) mappings: Vec<(PathBuf, String)>,
+    pub(crate) workspace_root: Option<PathBuf>,
+    pub(crate) shorten: bool,
+    pub(crate) source_remaps: Vec<(PathBuf, PathBuf)>,
+    pub(crate) source_roots: Vec<PathBuf>,
+    pub(crate) embedded_sources: EmbeddedSources,
+}
+
+impl PathRewriter {
+    /// The display form of `path`
+    pub(crate) fn rewrite<'p>(&self, path: &'p Path) -> Cow<'p, str> {
+        for (prefix, replacement) in &self.mappings {
+            if let Ok(rest) = path.strip_prefix(prefix) {
+                return Cow::Owned(join(replacement, rest));
+            }
+        }
+
+        if let Some(root) = self.workspace_root.as_ref() {
+            if let Ok(rest) = path.strip_prefix(root) {
+                return rest.to_string_lossy();
+            }
+        }
+
+        if self.shorten {
+            if let Some(short) = shorten(path) {
+                return Cow::Owned(short);
+            }
+        }
+
+        path.to_string_lossy()
+    }
+
+    /// The path the source file at `path` on the build machine can be read
+    /// from on this machine, if any
+    ///
+    /// Tries `path` itself, then the configured prefix remaps, then every
+    /// suffix of `path` down to its parent directory and file name under each
+    /// source root, longest first, and finally the same crate in the Cargo
+    /// registry under `$CARGO_HOME`.
+    pub(crate) fn find_source(&self, path: &Path) -> Option<PathBuf> {
+        if path.is_file() {
+            return Some(path.to_owned());
+        }
+
+        let remapped = self
+            .source_remaps
+            .iter()
+            .filter_map(|(from, to)| path.strip_prefix(from).ok().map(|rest| to.join(rest)));
+
+        let components: Vec<&OsStr> = path
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+        let components = &components;
+        // A bare file name like `mod.rs` or `lib.rs` would match the wrong file
+        // far too often.
+        let suffixes = 0..components.len().saturating_sub(1);
+        let rooted = self.source_roots.iter().flat_map(move |root| {
+            suffixes
+                .clone()
+                .map(move |i| root.join(rest(&components[i..])))
+        });
+
+        remapped
+            .chain(rooted)
+            .chain(registry_sources(path))
+            .find(|candidate| candidate.is_file())
+    }
+}
+
+/// Candidate paths for a file from the Cargo registry of the build machine in
+/// the Cargo registry of this machine
+fn registry_sources(path: &Path) -> Vec<PathBuf> {
+    let (krate, rest) = match registry_source(path) {
+        Some(source) => source,
+        None => return Vec::new(),
+    };
+
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".cargo"))
+        });
+    let registries = match cargo_home.map(|home| home.join("registry").join("src")) {
+        Some(registries) => registries,
+        None => return Vec::new(),
+    };
+
+    // The name of each registry directory contains a hash, which may differ
+    // between machines.
+    match std::fs::read_dir(registries) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path().join(krate).join(&rest))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Split a path of the form `<...>/registry/src/<registry>/<crate>-<version>/<rest>`
+/// into `<crate>-<version>` and `<rest>`
+fn registry_source(path: &Path) -> Option<(&OsStr, PathBuf)> {
+    let components: Vec<&OsStr> = path.iter().collect();
+
+    (0..components.len())
+        .find(|&i| {
+            components[i] == "registry"
+                && components.get(i + 1).is_some_and(|c| *c == "src")
+                && i + 3 < components.len()
+        })
+        .map(|i| (components[i + 3], rest(&components[i + 4..])))
+}
+
+/// Shorten paths into the Cargo registry to `[tokio-1.2.3]/src/...` and paths
+/// into the standard library sources to `[std]/core/src/...`
+fn shorten(path: &Path) -> Option<String> {
+    let components: Vec<&OsStr> = path.iter().collect();
+    let is = |i: usize, name: &str| components.get(i).is_some_and(|c| *c == name);
+
+    for i in 0..components.len() {
+        // `/rustc/<commit hash>/library/...` as embedded in the precompiled
+        // standard library, and `<sysroot>/lib/rustlib/src/rust/library/...`
+        // with the rust-src component installed.
+        let std_sources = is(i, "library")
+            && ((i >= 2 && is(i - 2, "rustc"))
+                || (i >= 3 && is(i - 1, "rust") && is(i - 2, "src") && is(i - 3, "rustlib")));
+        if std_sources {
+            return Some(join("[std]", &rest(&components[i + 1..])));
+        }
+    }
+
+    // `$CARGO_HOME/registry/src/<registry>/<crate>-<version>/...`
+    registry_source(path)
+        .map(|(krate, rest)| join(&format!("[{}]", krate.to_string_lossy()), &rest))
+}
+
+fn rest(components: &[&OsStr]) -> PathBuf {
+    components.iter().collect()
+}
+
+fn join(prefix: &str, rest: &Path) -> String {
+    if rest.as_os_str().is_empty() {
+        prefix.to_owned()
+    } else {
+        format!("{}/{}", prefix.trim_end_matches('/'), rest.display())
+    }
+}
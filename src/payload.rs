@@ -0,0 +1,59 @@
+//! Rendering of panic payloads, including payloads of custom types raised via
+//! `std::panic::panic_any`
+use crate::writers::Guarded;
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    fmt::{self, Write},
+};
+
+/// Callback rendering a panic payload of a registered type
+type FormatCallback = dyn Fn(&dyn Any, &mut fmt::Formatter<'_>) -> fmt::Result + Send + Sync;
+
+/// Formatters for panic payloads other than `String` and `&str`, see
+/// `HookBuilder::panic_payload_formatter`
+#[derive(Default)]
+pub(crate) struct PayloadFormatters(Vec<(TypeId, Box<FormatCallback>)>);
+
+impl PayloadFormatters {
+    pub(crate) fn register<T, F>(&mut self, format: F)
+    where
+        T: Any,
+        F: Fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result + Send + Sync + 'static,
+    {
+        // Later registrations for the same type take precedence.
+        self.0.retain(|(type_id, _)| *type_id != TypeId::of::<T>());
+        self.0.push((
+            TypeId::of::<T>(),
+            Box::new(move |payload, f| match payload.downcast_ref::<T>() {
+                Some(payload) => format(payload, f),
+                None => Ok(()),
+            }),
+        ));
+    }
+
+    /// The message of a panic with `payload`
+    pub(crate) fn message<'a>(&self, payload: &'a (dyn Any + Send)) -> Cow<'a, str> {
+        if let Some(message) = payload.downcast_ref::<String>() {
+            return Cow::Borrowed(message);
+        }
+
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            return Cow::Borrowed(message);
+        }
+
+        let payload: &dyn Any = payload;
+        let format = self
+            .0
+            .iter()
+            .find(|(type_id, _)| *type_id == payload.type_id());
+        if let Some((_, format)) = format {
+            let mut message = String::new();
+            if write!(&mut message, "{}", Guarded::new(|f| format(payload, f))).is_ok() {
+                return Cow::Owned(message);
+            }
+        }
+
+        Cow::Borrowed("<non string panic payload>")
+    }
+}
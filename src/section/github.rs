@@ -1,23 +1,26 @@
-use crate::writers::DisplayExt;
+use crate::{
+    markdown::{Body, Collapsed, ConsoleSection, DisplayFromDebug},
+    writers::DisplayExt,
+};
 use backtrace::Backtrace;
-use std::{fmt, panic::Location};
+use std::{borrow::Cow, fmt, panic::Location};
 use url::Url;
 
 type Display<'a> = Box<dyn std::fmt::Display + Send + Sync + 'a>;
 
 pub(crate) struct IssueSection<'a> {
     url: &'a str,
-    msg: &'a str,
+    msg: Cow<'a, str>,
     location: Option<&'a Location<'a>>,
     backtrace: Option<&'a Backtrace>,
     metadata: &'a [(String, Display<'a>)],
 }
 
 impl<'a> IssueSection<'a> {
-    pub(crate) fn new(url: &'a str, msg: &'a str) -> Self {
+    pub(crate) fn new(url: &'a str, msg: impl Into<Cow<'a, str>>) -> Self {
         IssueSection {
             url,
-            msg,
+            msg: msg.into(),
             location: None,
             backtrace: None,
             metadata: &[],
@@ -40,15 +43,36 @@ impl<'a> IssueSection<'a> {
     }
 }
 
-impl fmt::Display for IssueSection<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl IssueSection<'_> {
+    /// Build the issue url pre-populated with the information about this report
+    ///
+    /// Falls back to the bare repository url if the body fails to render or
+    /// the url fails to parse.
+    pub(crate) fn url(&self) -> String {
+        let body = match self.body() {
+            Ok(body) => body,
+            Err(_) => return self.url.to_string(),
+        };
+
+        let url_result = Url::parse_with_params(
+            self.url,
+            &[("title", "<autogenerated-issue>"), ("body", body.as_str())],
+        );
+
+        match url_result {
+            Ok(url_struct) => url_struct.into(),
+            Err(_) => self.url.to_string(),
+        }
+    }
+
+    fn body(&self) -> Result<Body, fmt::Error> {
         let location = self
             .location
             .map(|loc| ("location".to_string(), Box::new(loc) as _));
         let metadata = self.metadata.iter().chain(location.as_ref());
         let metadata = MetadataSection { metadata }.to_string();
         let mut body = Body::new();
-        body.push_section("Error", ConsoleSection(self.msg))?;
+        body.push_section("Error", ConsoleSection(&self.msg))?;
 
         if !self.metadata.is_empty() {
             body.push_section("Metadata", metadata)?;
@@ -63,44 +87,15 @@ impl fmt::Display for IssueSection<'_> {
             )?;
         }
 
-        let url_result = Url::parse_with_params(
-            self.url,
-            &[("title", "<autogenerated-issue>"), ("body", &body.body)],
-        );
-
-        let url: &dyn fmt::Display = match &url_result {
-            Ok(url_struct) => url_struct,
-            Err(_) => &self.url,
-        };
-
-        url.with_header("Consider reporting this error using this URL: ")
-            .fmt(f)
+        Ok(body)
     }
 }
 
-struct Body {
-    body: String,
-}
-
-impl Body {
-    fn new() -> Self {
-        Body {
-            body: String::new(),
-        }
-    }
-    fn push_section<T>(&mut self, header: &'static str, section: T) -> fmt::Result
-    where
-        T: fmt::Display,
-    {
-        use std::fmt::Write;
-
-        let separator = if self.body.is_empty() { "" } else { "\n\n" };
-        let header = header
-            .with_header("## ")
-            .with_header(separator)
-            .with_footer("\n");
-
-        write!(&mut self.body, "{}", section.with_header(header))
+impl fmt::Display for IssueSection<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.url()
+            .with_header("Consider reporting this error using this URL: ")
+            .fmt(f)
     }
 }
 
@@ -132,39 +127,3 @@ where
         out
     }
 }
-
-struct ConsoleSection<T>(T);
-
-impl<T> fmt::Display for ConsoleSection<T>
-where
-    T: fmt::Display,
-{
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        (&self.0).with_header("```\n").with_footer("\n```").fmt(f)
-    }
-}
-
-struct Collapsed<T>(T);
-
-impl<T> fmt::Display for Collapsed<T>
-where
-    T: fmt::Display,
-{
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        (&self.0)
-            .with_header("\n<details>\n\n")
-            .with_footer("\n</details>")
-            .fmt(f)
-    }
-}
-
-struct DisplayFromDebug<T>(T);
-
-impl<T> fmt::Display for DisplayFromDebug<T>
-where
-    T: fmt::Debug,
-{
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
-    }
-}
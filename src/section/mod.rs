@@ -326,5 +326,6 @@ pub trait Section: crate::private::Sealed {
 /// Trait for printing a panic error message for the given PanicInfo
 pub trait PanicMessage: Send + Sync + 'static {
     /// Display trait equivalent for implementing the display logic
+    #[allow(deprecated)]
     fn display(&self, pi: &std::panic::PanicInfo<'_>, f: &mut fmt::Formatter<'_>) -> fmt::Result;
 }
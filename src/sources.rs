@@ -0,0 +1,179 @@
+//! Source files compiled into the binary, for showing source snippets on
+//! machines without a source checkout
+use std::path::{Path, PathBuf};
+
+/// Source code compiled into the binary, which source snippets fall back to
+/// when a file is not on disk
+///
+/// Usually created with the [`embed_sources!`] macro, which embeds whole files
+/// of the invoking crate. Excerpts, for example the lines around known panic
+/// sites, can be added with [`EmbeddedSources::add_lines`] to keep the binary
+/// small.
+///
+/// Files are identified by the path they had when the binary was built.
+/// Cargo passes paths relative to the workspace root to the compiler, so a
+/// relative path in a backtrace is resolved against the roots added with
+/// [`EmbeddedSources::add_root`] and the `HookBuilder::workspace_root`, and
+/// only matches an embedded file with exactly that path.
+///
+/// [`embed_sources!`]: crate::embed_sources
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddedSources {
+    roots: Vec<PathBuf>,
+    files: Vec<EmbeddedFile>,
+}
+
+#[derive(Debug, Clone)]
+struct EmbeddedFile {
+    path: PathBuf,
+    first_line: u32,
+    lines: &'static str,
+}
+
+impl EmbeddedSources {
+    /// An empty set of embedded sources
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve relative paths in backtraces against `root`, the workspace root
+    /// on the build machine
+    ///
+    /// [`embed_sources!`] adds the directory of the invoking crate, which is the
+    /// workspace root unless the crate is a member of a larger workspace.
+    ///
+    /// [`embed_sources!`]: crate::embed_sources
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nocolor_eyre::config::EmbeddedSources;
+    ///
+    /// let sources = EmbeddedSources::new()
+    ///     .add_root("/build/workspace")
+    ///     .add_lines("/build/workspace/app/src/main.rs", 40, "    run()?;\n");
+    /// ```
+    pub fn add_root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    /// Add the complete contents of the file at `path` on the build machine
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nocolor_eyre::config::EmbeddedSources;
+    ///
+    /// let sources = EmbeddedSources::new().add_file(
+    ///     concat!(env!("CARGO_MANIFEST_DIR"), "/src/lib.rs"),
+    ///     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/lib.rs")),
+    /// );
+    /// ```
+    pub fn add_file<P: Into<PathBuf>>(self, path: P, contents: &'static str) -> Self {
+        self.add_lines(path, 1, contents)
+    }
+
+    /// Add an excerpt of the file at `path` on the build machine, starting at
+    /// line `first_line`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nocolor_eyre::config::EmbeddedSources;
+    ///
+    /// let sources = EmbeddedSources::new().add_lines(
+    ///     "/build/app/src/main.rs",
+    ///     40,
+    ///     "    let config = load_config()\n        .expect(\"config is valid\");\n",
+    /// );
+    /// ```
+    pub fn add_lines<P: Into<PathBuf>>(
+        mut self,
+        path: P,
+        first_line: u32,
+        lines: &'static str,
+    ) -> Self {
+        self.files.push(EmbeddedFile {
+            path: path.into(),
+            first_line: first_line.max(1),
+            lines,
+        });
+        self
+    }
+
+    /// The embedded lines of `path` from line `start` up to, but excluding,
+    /// line `end`, taken from the first embedded file with line `lineno`
+    ///
+    /// Relative paths are also resolved against `workspace_root`.
+    pub(crate) fn lines(
+        &self,
+        path: &Path,
+        workspace_root: Option<&Path>,
+        lineno: u32,
+        start: u32,
+        end: u32,
+    ) -> Vec<(u32, &'static str)> {
+        let candidates: Vec<PathBuf> = if path.is_relative() {
+            self.roots
+                .iter()
+                .map(PathBuf::as_path)
+                .chain(workspace_root)
+                .map(|root| root.join(path))
+                .collect()
+        } else {
+            vec![path.to_owned()]
+        };
+
+        let file = self
+            .files
+            .iter()
+            .find(|file| candidates.contains(&file.path) && file.line(lineno).is_some());
+
+        match file {
+            Some(file) => (start..end)
+                .filter_map(|lineno| Some((lineno, file.line(lineno)?)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl EmbeddedFile {
+    fn line(&self, lineno: u32) -> Option<&'static str> {
+        let index = lineno.checked_sub(self.first_line)?;
+        self.lines.lines().nth(index as usize)
+    }
+}
+
+/// Embed source files of the invoking crate into the binary
+///
+/// Paths are relative to the directory of the crate's `Cargo.toml`, which is
+/// also added as a root via [`EmbeddedSources::add_root`]. The
+/// resulting [`EmbeddedSources`] are installed with
+/// [`HookBuilder::embedded_sources`], after which source snippets are shown for
+/// these files even where they are not on disk.
+///
+/// [`EmbeddedSources`]: crate::config::EmbeddedSources
+/// [`EmbeddedSources::add_root`]: crate::config::EmbeddedSources::add_root
+/// [`HookBuilder::embedded_sources`]: crate::config::HookBuilder::embedded_sources
+///
+/// # Examples
+///
+/// ```rust
+/// nocolor_eyre::config::HookBuilder::default()
+///     .embedded_sources(nocolor_eyre::embed_sources!("src/lib.rs", "src/config.rs"))
+///     .install()
+///     .unwrap();
+/// ```
+#[macro_export]
+macro_rules! embed_sources {
+    ($($path:literal),* $(,)?) => {
+        $crate::config::EmbeddedSources::new()
+            .add_root(env!("CARGO_MANIFEST_DIR"))
+            $(.add_file(
+                concat!(env!("CARGO_MANIFEST_DIR"), "/", $path),
+                include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)),
+            ))*
+    };
+}
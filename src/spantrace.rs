@@ -0,0 +1,128 @@
+//! Colorless rendering of `tracing_error::SpanTrace`s
+use crate::{
+    config::{lib_verbosity, panic_verbosity, Charset, Frame, SourceSection, Verbosity},
+    paths::PathRewriter,
+    writers::WriterExt,
+};
+use std::{
+    fmt::{self, Write},
+    path::Path,
+};
+use tracing_error::{ExtractSpanTrace, SpanTrace, SpanTraceStatus};
+
+/// Find the innermost `SpanTrace` already captured by an error in the chain
+pub(crate) fn get_deepest_spantrace<'a>(
+    error: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a SpanTrace> {
+    eyre::Chain::new(error)
+        .rev()
+        .flat_map(|error| error.span_trace())
+        .next()
+}
+
+pub(crate) struct SpanTraceFormatter<'a>(
+    pub(crate) &'a SpanTrace,
+    pub(crate) &'a PathRewriter,
+    pub(crate) Charset,
+);
+
+impl fmt::Display for SpanTraceFormatter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(span_trace, paths, charset) = self;
+
+        if span_trace.status() != SpanTraceStatus::CAPTURED {
+            return Ok(());
+        }
+
+        write!(f, "[SPANTRACE]")?;
+
+        let v = if std::thread::panicking() {
+            panic_verbosity()
+        } else {
+            lib_verbosity()
+        };
+
+        let mut separated = f.header("\n");
+        let mut result = Ok(());
+        let mut n = 0;
+
+        span_trace.with_spans(|metadata, fields| {
+            result = write!(
+                &mut separated.ready(),
+                "{}",
+                StyledSpan {
+                    n,
+                    target: metadata.target(),
+                    name: metadata.name(),
+                    file: metadata.file(),
+                    lineno: metadata.line(),
+                    fields,
+                    verbosity: v,
+                    paths,
+                    charset: *charset,
+                }
+            );
+            n += 1;
+
+            result.is_ok()
+        });
+
+        result
+    }
+}
+
+struct StyledSpan<'a> {
+    n: usize,
+    target: &'a str,
+    name: &'a str,
+    file: Option<&'a str>,
+    lineno: Option<u32>,
+    fields: &'a str,
+    verbosity: Verbosity,
+    paths: &'a PathRewriter,
+    charset: Charset,
+}
+
+impl fmt::Display for StyledSpan<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Print span index, target and name.
+        write!(f, "{:>2}: {}::{}", self.n, self.target, self.name)?;
+
+        if !self.fields.is_empty() {
+            write!(f, " with {}", self.fields)?;
+        }
+
+        let mut separated = f.header("\n");
+
+        // Print source location, if known.
+        if let Some(file) = self.file {
+            let lineno = self
+                .lineno
+                .map_or("<unknown line>".to_owned(), |x| x.to_string());
+            let file = self.paths.rewrite(Path::new(file));
+            write!(&mut separated.ready(), "    at {}:{}", file, lineno)?;
+        }
+
+        // Maybe print source.
+        if self.verbosity >= Verbosity::Full {
+            let frame = Frame {
+                n: self.n,
+                index: self.n,
+                inlined: false,
+                name: Some(self.name.to_owned()),
+                lineno: self.lineno,
+                colno: None,
+                filename: self.file.map(Into::into),
+                ip: None,
+                module_base: None,
+            };
+            write!(
+                &mut separated.ready(),
+                "{}",
+                SourceSection(&frame, self.paths, self.charset)
+            )?;
+        }
+
+        Ok(())
+    }
+}
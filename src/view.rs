@@ -0,0 +1,249 @@
+//! Read-only access to the contents of error reports
+use crate::{config::Frame, section::help::HelpInfo, Handler};
+use std::fmt::{self, Display};
+
+/// A read-only view of an `eyre::Report` and the information its [`Handler`]
+/// collected
+///
+/// # Details
+///
+/// This exposes the same data the text report is rendered from, so that it can
+/// be inspected or rendered by other means without parsing the formatted
+/// output.
+///
+/// # Examples
+///
+/// ```rust
+/// use nocolor_eyre::{eyre::eyre, ReportSection, ReportView, Section};
+///
+/// nocolor_eyre::install().unwrap();
+///
+/// let report = eyre!("inner error")
+///     .wrap_err("outer error")
+///     .note("a note");
+///
+/// let view = ReportView::new(&report).unwrap();
+/// let errors: Vec<_> = view.chain().map(|error| error.to_string()).collect();
+/// assert_eq!(errors, ["outer error", "inner error"]);
+///
+/// if let Some(ReportSection::Note(note)) = view.sections().next() {
+///     assert_eq!(note.to_string(), "a note");
+/// }
+/// ```
+///
+/// [`Handler`]: crate::Handler
+#[derive(Debug)]
+pub struct ReportView<'a> {
+    error: &'a (dyn std::error::Error + 'static),
+    handler: &'a Handler,
+}
+
+impl<'a> ReportView<'a> {
+    /// Create a view of `report`
+    ///
+    /// Returns `None` if the report was not created by the handler of this
+    /// crate, e.g. because the hooks were not installed before it was created.
+    pub fn new(report: &'a eyre::Report) -> Option<Self> {
+        let handler = report.handler().downcast_ref::<Handler>()?;
+
+        Some(ReportView {
+            error: report.as_ref(),
+            handler,
+        })
+    }
+
+    /// The `Handler` of the viewed report
+    pub fn handler(&self) -> &'a Handler {
+        self.handler
+    }
+
+    /// Iterate over the chain of errors, starting with the outermost one
+    pub fn chain(&self) -> eyre::Chain<'a> {
+        eyre::Chain::new(self.error)
+    }
+
+    /// Iterate over the sections attached to the report, in the order they were
+    /// added
+    pub fn sections(&self) -> Sections<'a> {
+        Sections(self.handler.sections.iter())
+    }
+
+    /// The location the report was created at, as tracked via
+    /// `#[track_caller]`, or the location of the panic for reports created by
+    /// `catch_panic`
+    ///
+    /// This is the location the location section points at, with the path
+    /// as recorded by the compiler.
+    pub fn location(&self) -> Option<ReportLocation<'a>> {
+        if let Some(location) = self.handler.panic_location.as_ref() {
+            return Some(ReportLocation {
+                file: &location.file,
+                line: location.line,
+                column: location.column,
+            });
+        }
+
+        #[cfg(feature = "track-caller")]
+        if let Some(location) = self.handler.location {
+            return Some(ReportLocation {
+                file: location.file(),
+                line: location.line(),
+                column: location.column(),
+            });
+        }
+
+        None
+    }
+
+    /// The backtrace frames remaining after applying the configured frame
+    /// filters, or `None` if no backtrace was captured
+    pub fn frames(&self) -> Option<Vec<Frame>> {
+        let backtrace = self.handler.backtrace.as_ref()?;
+        let formatter = self.handler.format_backtrace(backtrace);
+        let frames = formatter.frames();
+        let filtered = formatter.filter(&frames).into_iter().cloned().collect();
+
+        Some(filtered)
+    }
+
+    /// Whether capturing the backtrace was skipped because the capture budget
+    /// configured via `HookBuilder::capture_budget` was used up
+    pub fn backtrace_rate_limited(&self) -> bool {
+        self.handler.backtrace_rate_limited
+    }
+
+    /// Whether printing of the backtrace was suppressed via
+    /// `Section::suppress_backtrace`
+    pub fn backtrace_suppressed(&self) -> bool {
+        self.handler.suppress_backtrace
+    }
+
+    /// Whether the environment variable info section is displayed
+    pub fn display_env_section(&self) -> bool {
+        self.handler.display_env_section
+    }
+
+    /// Render the report as Markdown, regardless of the configured
+    /// `ReportFormat`
+    ///
+    /// Returns an empty string if one of the errors fails to render.
+    pub fn markdown(&self) -> String {
+        crate::markdown::report(self.handler, self.error).unwrap_or_default()
+    }
+
+    /// Render the report as a standalone HTML document, regardless of the
+    /// configured `ReportFormat`
+    ///
+    /// Returns an empty string if one of the errors fails to render.
+    pub fn html(&self) -> String {
+        crate::html::report(self.handler, self.error).unwrap_or_default()
+    }
+
+    /// Whether the location section is displayed
+    #[cfg(feature = "track-caller")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "track-caller")))]
+    pub fn display_location_section(&self) -> bool {
+        self.handler.display_location_section
+    }
+}
+
+/// The source location of a report, see [`ReportView::location`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportLocation<'a> {
+    file: &'a str,
+    line: u32,
+    column: u32,
+}
+
+impl<'a> ReportLocation<'a> {
+    /// The path of the source file
+    pub fn file(&self) -> &'a str {
+        self.file
+    }
+
+    /// The line number, starting at 1
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// The column number, starting at 1
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+}
+
+impl Display for ReportLocation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Iterator over the sections of a report, see [`ReportView::sections`]
+#[derive(Debug, Clone)]
+pub struct Sections<'a>(std::slice::Iter<'a, HelpInfo>);
+
+impl<'a> Iterator for Sections<'a> {
+    type Item = ReportSection<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(ReportSection::from)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// A section attached to an error report via the [`Section`] trait
+///
+/// [`Section`]: crate::Section
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub enum ReportSection<'a> {
+    /// An error added via `Section::error`
+    Error(&'a (dyn std::error::Error + Send + Sync + 'static)),
+    /// A custom section added via `Section::section`
+    Custom(&'a (dyn Display + Send + Sync + 'static)),
+    /// A note added via `Section::note`
+    Note(&'a (dyn Display + Send + Sync + 'static)),
+    /// A warning added via `Section::warning`
+    Warning(&'a (dyn Display + Send + Sync + 'static)),
+    /// A suggestion added via `Section::suggestion`
+    Suggestion(&'a (dyn Display + Send + Sync + 'static)),
+}
+
+impl fmt::Debug for ReportSection<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportSection::Error(error) => f.debug_tuple("Error").field(error).finish(),
+            ReportSection::Custom(custom) => f
+                .debug_tuple("Custom")
+                .field(&format_args!("{}", custom))
+                .finish(),
+            ReportSection::Note(note) => f
+                .debug_tuple("Note")
+                .field(&format_args!("{}", note))
+                .finish(),
+            ReportSection::Warning(warning) => f
+                .debug_tuple("Warning")
+                .field(&format_args!("{}", warning))
+                .finish(),
+            ReportSection::Suggestion(suggestion) => f
+                .debug_tuple("Suggestion")
+                .field(&format_args!("{}", suggestion))
+                .finish(),
+        }
+    }
+}
+
+impl<'a> From<&'a HelpInfo> for ReportSection<'a> {
+    fn from(section: &'a HelpInfo) -> Self {
+        match section {
+            HelpInfo::Error(error) => ReportSection::Error(error.as_ref()),
+            HelpInfo::Custom(section) => ReportSection::Custom(section.as_ref()),
+            HelpInfo::Note(note) => ReportSection::Note(note.as_ref()),
+            HelpInfo::Warning(warning) => ReportSection::Warning(warning.as_ref()),
+            HelpInfo::Suggestion(suggestion) => ReportSection::Suggestion(suggestion.as_ref()),
+        }
+    }
+}
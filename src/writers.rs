@@ -1,5 +1,6 @@
 use crate::config::{lib_verbosity, panic_verbosity, Verbosity};
 use fmt::Write;
+use std::cell::Cell;
 use std::fmt::{self, Display};
 
 #[allow(explicit_outlives_requirements)]
@@ -149,8 +150,99 @@ where
     }
 }
 
+thread_local! {
+    static GUARDING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the current thread is rendering user supplied code via `Guarded`
+///
+/// The panic hook doesn't report panics raised while this is the case, as
+/// they are replaced by a placeholder in the report being rendered.
+pub(crate) fn guarding() -> bool {
+    GUARDING.try_with(Cell::get).unwrap_or(false)
+}
+
+/// Renders user supplied code, or a placeholder if it panics
+///
+/// The output is buffered so that a panic never leaves a partially rendered
+/// section behind. Panics can't be caught while a panic hook runs, as the
+/// standard library aborts the process instead, so panic reports rely on
+/// `PanicHook` itself not panicking.
+pub(crate) struct Guarded<F>(F);
+
+impl<F> Guarded<F>
+where
+    F: Fn(&mut fmt::Formatter<'_>) -> fmt::Result,
+{
+    pub(crate) fn new(render: F) -> Self {
+        Self(render)
+    }
+}
+
+impl<F> fmt::Display for Guarded<F>
+where
+    F: Fn(&mut fmt::Formatter<'_>) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Render<'a, F>(&'a F);
+
+        impl<F> fmt::Display for Render<'_, F>
+        where
+            F: Fn(&mut fmt::Formatter<'_>) -> fmt::Result,
+        {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                (self.0)(f)
+            }
+        }
+
+        let mut rendered = String::new();
+        let guarding = GUARDING.try_with(|guarding| guarding.replace(true));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            write!(&mut rendered, "{}", Render(&self.0))
+        }));
+        if let Ok(guarding) = guarding {
+            GUARDING.with(|outer| outer.set(guarding));
+        }
+
+        match result {
+            Ok(Ok(())) => f.write_str(&rendered),
+            Ok(Err(e)) => Err(e),
+            Err(_) => f.write_str("<this section panicked while being rendered>"),
+        }
+    }
+}
+
 pub(crate) struct EnvSection<'a> {
     pub(crate) bt_captured: &'a bool,
+    /// Whether the backtrace was skipped because the capture budget was used up
+    pub(crate) bt_rate_limited: bool,
+}
+
+impl EnvSection<'_> {
+    fn backtrace_omitted(&self) -> BacktraceOmited {
+        BacktraceOmited {
+            omitted: !self.bt_captured,
+            rate_limited: self.bt_rate_limited,
+        }
+    }
+
+    /// The individual hints of this section, in the order they are displayed
+    pub(crate) fn hints(&self) -> Vec<String> {
+        let v = if std::thread::panicking() {
+            panic_verbosity()
+        } else {
+            lib_verbosity()
+        };
+
+        vec![
+            self.backtrace_omitted().to_string(),
+            FrameFilterEnv(*self.bt_captured).to_string(),
+            SourceSnippets(v).to_string(),
+        ]
+        .into_iter()
+        .filter(|hint| !hint.is_empty())
+        .collect()
+    }
 }
 
 impl fmt::Display for EnvSection<'_> {
@@ -160,24 +252,37 @@ impl fmt::Display for EnvSection<'_> {
         } else {
             lib_verbosity()
         };
-        write!(f, "{}", BacktraceOmited(!self.bt_captured))?;
+        write!(f, "{}", self.backtrace_omitted())?;
 
         let mut separated = HeaderWriter {
             inner: &mut *f,
             header: &"\n",
             started: false,
         };
+        write!(
+            &mut separated.ready(),
+            "{}",
+            FrameFilterEnv(*self.bt_captured)
+        )?;
         write!(&mut separated.ready(), "{}", SourceSnippets(v))?;
         Ok(())
     }
 }
 
-struct BacktraceOmited(bool);
+struct BacktraceOmited {
+    omitted: bool,
+    rate_limited: bool,
+}
 
 impl fmt::Display for BacktraceOmited {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // Print some info on how to increase verbosity.
-        if self.0 {
+        if self.omitted && self.rate_limited {
+            write!(
+                f,
+                "Backtrace omitted by the rate limiter because the backtrace capture budget was used up."
+            )?;
+        } else if self.omitted {
+            // Print some info on how to increase verbosity.
             write!(
                 f,
                 "Backtrace omitted. Run with RUST_BACKTRACE=1 environment variable to display it."
@@ -194,6 +299,25 @@ impl fmt::Display for BacktraceOmited {
     }
 }
 
+/// Hint at the environment variables adjusting frame filtering, if frames
+/// are displayed
+struct FrameFilterEnv(bool);
+
+impl fmt::Display for FrameFilterEnv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 {
+            write!(
+                f,
+                "Run with {}=<prefixes> or {}=<prefixes> environment variables to hide or show frames by comma separated symbol prefixes.",
+                crate::filter::HIDE_ENV,
+                crate::filter::SHOW_ENV,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
 struct SourceSnippets(Verbosity);
 
 impl fmt::Display for SourceSnippets {
@@ -0,0 +1,44 @@
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report},
+};
+
+#[inline(never)]
+fn recurse(depth: usize) -> Report {
+    let report = if depth == 0 {
+        eyre!("error")
+    } else {
+        recurse(depth - 1)
+    };
+
+    std::hint::black_box(report)
+}
+
+#[test]
+fn ascii_output_replaces_decorations() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "full");
+    std::env::remove_var("COLORBT_SHOW_HIDDEN");
+    std::env::remove_var("NOCOLOR_EYRE_ASCII");
+
+    HookBuilder::default().ascii_output(true).install().unwrap();
+
+    let report = format!("{:?}", recurse(50));
+
+    assert!(report.is_ascii(), "{}", report);
+    assert!(report.contains("\n      hidden frames: "), "{}", report);
+    assert!(
+        report.contains("\n      repeated frames: previous 1 frame repeated"),
+        "{}",
+        report
+    );
+    assert!(report.contains(" | "), "{}", report);
+    assert!(report.contains(" >         eyre!(\"error\")"), "{}", report);
+
+    // The environment variable takes precedence over the builder.
+    std::env::set_var("NOCOLOR_EYRE_ASCII", "0");
+    let report = format!("{:?}", recurse(0));
+    std::env::remove_var("NOCOLOR_EYRE_ASCII");
+
+    assert!(report.contains(" frames hidden ⋮"), "{}", report);
+    assert!(report.contains(" │ "), "{}", report);
+}
@@ -0,0 +1,31 @@
+use nocolor_eyre::{
+    config::{CaptureBudget, HookBuilder},
+    eyre::eyre,
+    ReportView,
+};
+
+#[test]
+fn backtraces_are_sampled() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    HookBuilder::default()
+        .capture_budget(CaptureBudget::Sample(2))
+        .install()
+        .unwrap();
+
+    let reports: Vec<_> = (0..4).map(|_| eyre!("error")).collect();
+    let captured: Vec<_> = reports
+        .iter()
+        .map(|report| ReportView::new(report).unwrap())
+        .map(|view| (view.frames().is_some(), view.backtrace_rate_limited()))
+        .collect();
+
+    assert_eq!(
+        captured,
+        vec![(true, false), (false, true), (true, false), (false, true)]
+    );
+
+    let report = format!("{:?}", reports[1]);
+    assert!(report.contains("rate limiter"), "{}", report);
+    assert!(!report.contains("Run with RUST_BACKTRACE=1"), "{}", report);
+}
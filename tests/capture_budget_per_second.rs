@@ -0,0 +1,31 @@
+use nocolor_eyre::{
+    config::{CaptureBudget, HookBuilder},
+    eyre::eyre,
+    ReportView,
+};
+use std::time::Duration;
+
+#[test]
+fn backtraces_are_limited_per_second() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    HookBuilder::default()
+        .capture_budget(CaptureBudget::PerSecond(2))
+        .install()
+        .unwrap();
+
+    let captured = |report: &nocolor_eyre::Report| {
+        let view = ReportView::new(report).unwrap();
+        (view.frames().is_some(), view.backtrace_rate_limited())
+    };
+
+    let reports: Vec<_> = (0..3).map(|_| eyre!("error")).collect();
+    assert_eq!(
+        reports.iter().map(captured).collect::<Vec<_>>(),
+        vec![(true, false), (true, false), (false, true)]
+    );
+
+    // The budget is replenished once the window is over.
+    std::thread::sleep(Duration::from_millis(1100));
+    assert_eq!(captured(&eyre!("error")), (true, false));
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::SharedBuffer;
+use nocolor_eyre::{
+    config::{CaptureBudget, HookBuilder},
+    eyre::eyre,
+    PanicError, ReportView,
+};
+use std::sync::Mutex;
+
+static PANIC_MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[inline(never)]
+fn failing_job() -> u32 {
+    panic!("job failed")
+}
+
+#[test]
+fn panics_become_reports_with_panic_context() {
+    std::env::set_var("RUST_BACKTRACE", "1");
+
+    let panics = SharedBuffer::default();
+    HookBuilder::default()
+        .panic_section("consider restarting the worker")
+        .capture_budget(CaptureBudget::Sample(2))
+        .display_thread_section(true)
+        .add_panic_writer(panics.clone())
+        .add_panic_callback(|report| {
+            let message = report.payload_message().into_owned();
+            PANIC_MESSAGES.lock().unwrap().push(message);
+        })
+        .install()
+        .unwrap();
+
+    assert_eq!(nocolor_eyre::catch_panic(|| 42).unwrap(), 42);
+
+    let report = std::thread::Builder::new()
+        .name("worker".into())
+        .spawn(|| nocolor_eyre::catch_panic(failing_job).unwrap_err())
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let error = report.downcast_ref::<PanicError>().unwrap();
+    assert_eq!(error.message(), "job failed");
+
+    let rendered = format!("{:?}", report);
+    assert!(rendered.contains("0: job failed"), "{}", rendered);
+    assert!(
+        rendered.contains("Location:\n   tests/catch_panic.rs:15"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("Thread:\n   worker ("), "{}", rendered);
+    assert!(
+        rendered.contains("consider restarting the worker"),
+        "{}",
+        rendered
+    );
+    // The backtrace is the one of the panicking code, not of `catch_panic`.
+    assert!(
+        rendered.contains("catch_panic::failing_job"),
+        "{}",
+        rendered
+    );
+
+    // Converting the panic doesn't capture a backtrace of its own, which
+    // would have used up the capture budget.
+    let view = ReportView::new(&report).unwrap();
+    assert!(!view.backtrace_rate_limited());
+    let report = eyre!("next error");
+    assert!(ReportView::new(&report).unwrap().frames().is_some());
+
+    // Every panic is reported by the panic hook as usual, only the one that
+    // unwinds out of `catch_panic` becomes a report.
+    let value = nocolor_eyre::catch_panic(|| {
+        let _ = std::panic::catch_unwind(|| panic!("handled by the job"));
+        42
+    });
+    assert_eq!(value.unwrap(), 42);
+
+    let report = nocolor_eyre::catch_panic(|| {
+        let _ = std::panic::catch_unwind(|| panic!("retried by the job"));
+        panic!("job gave up")
+    })
+    .unwrap_err();
+    assert_eq!(report.to_string(), "job gave up");
+
+    let panics = panics.contents();
+    assert!(panics.contains("Message:  job failed"), "{}", panics);
+    assert!(panics.contains("Message:  job gave up"), "{}", panics);
+    assert_eq!(
+        *PANIC_MESSAGES.lock().unwrap(),
+        [
+            "job failed",
+            "handled by the job",
+            "retried by the job",
+            "job gave up"
+        ]
+    );
+}
+
+#[cfg(feature = "track-caller")]
+#[test]
+fn caught_panics_respect_the_location_flag() {
+    const CHILD_ENV: &str = "NOCOLOR_EYRE_CATCH_PANIC_CHILD";
+
+    if std::env::var_os(CHILD_ENV).is_some() {
+        HookBuilder::default()
+            .display_location_section(false)
+            .install()
+            .unwrap();
+
+        let report = nocolor_eyre::catch_panic(failing_job).unwrap_err();
+        // Printed to stdout, as the panic report goes to stderr.
+        println!("{:?}", report);
+        return;
+    }
+
+    // The panic hook is installed once per process, so the check runs in a
+    // child process with its own configuration.
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "caught_panics_respect_the_location_flag",
+            "--nocapture",
+        ])
+        .env(CHILD_ENV, "1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("0: job failed"), "{}", stdout);
+    assert!(!stdout.contains("Location:"), "{}", stdout);
+}
@@ -0,0 +1,32 @@
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report},
+};
+
+#[inline(never)]
+fn recurse(depth: usize) -> Report {
+    let report = if depth == 0 {
+        eyre!("error")
+    } else {
+        recurse(depth - 1)
+    };
+
+    std::hint::black_box(report)
+}
+
+#[test]
+fn repeated_frames_are_collapsed() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    std::env::remove_var("COLORBT_SHOW_HIDDEN");
+
+    HookBuilder::default().install().unwrap();
+
+    let report = format!("{:?}", recurse(50));
+
+    assert!(report.contains("⋮ previous 1 frame repeated"), "{}", report);
+    assert!(
+        report.matches("collapse_recursion::recurse").count() < 5,
+        "{}",
+        report
+    );
+}
@@ -0,0 +1,44 @@
+//! Fixtures shared by the integration tests
+//!
+//! Every test binary compiles its own copy of this module and only uses some
+//! of it.
+#![allow(dead_code)]
+
+use std::{
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+/// A panic writer whose output can be read back by the test
+#[derive(Clone, Default)]
+pub struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    /// Everything written so far
+    pub fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The `/rustc/<commit hash>/library/` directory the precompiled standard
+/// library was built from, if backtraces point into it
+///
+/// These sources don't exist on disk, which makes them stand in for the
+/// sources of a deployed binary.
+pub fn std_library_dir() -> Option<PathBuf> {
+    let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+    let start = backtrace.find("/rustc/")?;
+    let end = start + backtrace[start..].find("/library/")? + "/library/".len();
+    Some(PathBuf::from(&backtrace[start..end]))
+}
@@ -0,0 +1,32 @@
+use nocolor_eyre::config::HookBuilder;
+
+#[test]
+fn crash_reports_are_written_and_pruned() {
+    let dir = std::env::temp_dir().join(format!("nocolor-eyre-crashes-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    HookBuilder::default()
+        .crash_report_dir(&dir)
+        .max_crash_reports(2)
+        .install()
+        .unwrap();
+
+    for n in 0..3 {
+        std::thread::spawn(move || panic!("crash number {}", n))
+            .join()
+            .unwrap_err();
+    }
+
+    let mut reports: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    reports.sort();
+
+    assert_eq!(reports.len(), 2);
+    let newest = std::fs::read_to_string(&reports[1]).unwrap();
+    assert!(newest.contains("The application panicked (crashed)"));
+    assert!(newest.contains("crash number 2"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
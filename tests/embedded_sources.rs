@@ -0,0 +1,41 @@
+mod common;
+
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report},
+};
+
+#[inline(never)]
+fn create_report() -> Report {
+    eyre!("error")
+}
+
+#[test]
+fn snippets_fall_back_to_embedded_sources() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "full");
+    std::env::set_var("COLORBT_SHOW_HIDDEN", "1");
+
+    let library = match common::std_library_dir() {
+        Some(library) => library,
+        None => return,
+    };
+
+    let excerpt: String = (100..=2000)
+        .map(|i| format!("embedded line {}\n", i))
+        .collect();
+    let sources = nocolor_eyre::embed_sources!("tests/embedded_sources.rs").add_lines(
+        library.join("core/src/ops/function.rs"),
+        100,
+        Box::leak(excerpt.into()),
+    );
+    HookBuilder::default()
+        .embedded_sources(sources)
+        .install()
+        .unwrap();
+
+    let report = format!("{:?}", create_report());
+
+    assert!(report.contains("> embedded line "), "{}", report);
+    // Sources on disk are still read from disk.
+    assert!(report.contains(">     eyre!(\"error\")"), "{}", report);
+}
@@ -0,0 +1,75 @@
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report, WrapErr},
+};
+use std::process::{Command, ExitCode};
+
+const CHILD_ENV: &str = "NOCOLOR_EYRE_EXIT_CODE_CHILD";
+
+#[derive(Debug)]
+struct UsageError;
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("invalid usage")
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+fn child(scenario: &str) -> ExitCode {
+    let scenario = scenario.to_owned();
+
+    HookBuilder::default()
+        .exit_code_rule(|_: &UsageError| 64)
+        .exit_code_rule(|_: &std::io::Error| 74)
+        .panic_exit_code(70)
+        .run(move || -> Result<(), Report> {
+            match scenario.as_str() {
+                "ok" => Ok(()),
+                "usage" => Err(Report::new(UsageError)),
+                "io" => Err(std::io::Error::other("disk on fire")).wrap_err("failed to save"),
+                "other" => Err(eyre!("unclassified")),
+                _ => panic!("scenario panicked"),
+            }
+        })
+}
+
+fn run(scenario: &str) -> (Option<i32>, String) {
+    let output = Command::new(std::env::current_exe().unwrap())
+        .env(CHILD_ENV, scenario)
+        .output()
+        .unwrap();
+
+    (
+        output.status.code(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+/// Runs without the test harness, so that the exit code `HookBuilder::run`
+/// returns is the exit code of the child process.
+fn main() -> ExitCode {
+    if let Some(scenario) = std::env::var_os(CHILD_ENV) {
+        return child(scenario.to_str().unwrap());
+    }
+
+    assert_eq!(run("ok").0, Some(0));
+
+    let (code, stderr) = run("usage");
+    assert_eq!(code, Some(64));
+    assert!(stderr.starts_with("   0: invalid usage"), "{}", stderr);
+    assert!(!stderr.contains("Error: "), "{}", stderr);
+
+    let (code, stderr) = run("io");
+    assert_eq!(code, Some(74));
+    assert!(stderr.contains("1: disk on fire"), "{}", stderr);
+
+    assert_eq!(run("other").0, Some(1));
+
+    let (code, stderr) = run("panic");
+    assert_eq!(code, Some(70));
+    assert!(stderr.contains("Message:  scenario panicked"), "{}", stderr);
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,35 @@
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report},
+    ReportView,
+};
+
+#[inline(never)]
+fn create_report() -> Report {
+    eyre!("error")
+}
+
+#[test]
+fn env_overrides_adjust_filtering() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    std::env::set_var("NOCOLOR_EYRE_HIDE", "frame_env_overrides::create_report");
+    std::env::set_var("NOCOLOR_EYRE_SHOW", "eyre::, nocolor_eyre::");
+
+    HookBuilder::default().install().unwrap();
+
+    let report = create_report();
+    let frames = ReportView::new(&report).unwrap().frames().unwrap();
+    let names: Vec<_> = frames
+        .iter()
+        .filter_map(|frame| frame.name.as_ref())
+        .collect();
+
+    assert!(!names
+        .iter()
+        .any(|name| name.starts_with("frame_env_overrides::create_report")));
+    assert!(names.iter().any(|name| name.starts_with("nocolor_eyre::")));
+    assert!(frames.windows(2).all(|pair| pair[0].n < pair[1].n));
+
+    let report = format!("{:?}", report);
+    assert!(report.contains("NOCOLOR_EYRE_HIDE"));
+}
@@ -0,0 +1,46 @@
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report},
+    ReportView,
+};
+
+#[inline(never)]
+fn create_report() -> Report {
+    eyre!("error")
+}
+
+#[test]
+fn frames_carry_physical_frame_info() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    std::env::set_var("COLORBT_SHOW_HIDDEN", "1");
+
+    HookBuilder::blank().install().unwrap();
+
+    let report = create_report();
+    let frames = ReportView::new(&report).unwrap().frames().unwrap();
+
+    assert!(frames.iter().all(|frame| frame.ip.is_some()));
+    assert!(frames.windows(2).all(|pair| pair[0].index <= pair[1].index));
+    assert!(frames
+        .windows(2)
+        .all(|pair| !pair[0].inlined || pair[0].index == pair[1].index));
+
+    let frame = frames
+        .iter()
+        .find(|frame| {
+            frame
+                .name
+                .as_deref()
+                .is_some_and(|name| name.starts_with("frame_numbering::create_report"))
+        })
+        .unwrap();
+    assert_eq!(frame.lineno, Some(9));
+    assert_eq!(frame.colno, Some(5));
+
+    let report = format!("{:?}", report);
+    assert!(
+        report.contains("tests/frame_numbering.rs:9:5"),
+        "{}",
+        report
+    );
+}
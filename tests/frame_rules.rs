@@ -0,0 +1,44 @@
+use nocolor_eyre::{
+    config::{FrameRule, HookBuilder},
+    eyre::{eyre, Report},
+    ReportView,
+};
+
+#[inline(never)]
+fn hidden_helper() -> Report {
+    eyre!("error")
+}
+
+#[inline(never)]
+fn create_report() -> Report {
+    hidden_helper()
+}
+
+#[test]
+fn rules_hide_frames() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    HookBuilder::default()
+        .keep_only_crates(&["frame-rules"])
+        .hide_symbol_prefix("frame_rules::hidden_helper")
+        .add_frame_rule(FrameRule::HidePath("**/library/std/**".into()))
+        .install()
+        .unwrap();
+
+    let report = create_report();
+    let frames = ReportView::new(&report).unwrap().frames().unwrap();
+
+    assert!(frames
+        .iter()
+        .all(|frame| frame.crate_name() == Some("frame_rules")));
+    assert!(frames.iter().any(|frame| frame
+        .name
+        .as_ref()
+        .unwrap()
+        .starts_with("frame_rules::create_report")));
+    assert!(!frames.iter().any(|frame| frame
+        .name
+        .as_ref()
+        .unwrap()
+        .starts_with("frame_rules::hidden_helper")));
+}
@@ -0,0 +1,86 @@
+use nocolor_eyre::{
+    config::{HookBuilder, ReportFormat},
+    eyre::eyre,
+    ReportView, Section,
+};
+
+#[test]
+fn reports_render_as_html() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    std::env::set_var("RUST_BACKTRACE", "1");
+
+    let dir = std::env::temp_dir().join(format!("nocolor-eyre-html-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    HookBuilder::default()
+        .error_format(ReportFormat::Html)
+        .crash_report_dir(&dir)
+        .crash_report_format(ReportFormat::Html)
+        .install()
+        .unwrap();
+
+    let report = eyre!("<inner> & error")
+        .wrap_err("outer \"error\"")
+        .note("try <again>");
+    let html = format!("{:?}", report);
+
+    assert!(html.starts_with("<!DOCTYPE html>\n"), "{}", html);
+    assert!(html.ends_with("</html>\n"), "{}", html);
+    assert!(
+        html.contains(
+            "<ol class=\"chain\" start=\"0\">\n\
+             <li>outer &quot;error&quot;</li>\n\
+             <li>&lt;inner&gt; &amp; error</li>\n\
+             </ol>\n"
+        ),
+        "{}",
+        html
+    );
+    #[cfg(feature = "track-caller")]
+    assert!(
+        html.contains("<h2>Location</h2>\n<p><code>tests/html.rs:"),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains("<details>\n<summary>Backtrace</summary>\n"),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains("<details class=\"hidden\">\n<summary>"),
+        "{}",
+        html
+    );
+    // Source snippets are only shown with RUST_LIB_BACKTRACE=full.
+    assert!(!html.contains("</code>\n<pre>"), "{}", html);
+    assert!(
+        html.contains("<li><strong>Note:</strong> try &lt;again&gt;</li>"),
+        "{}",
+        html
+    );
+    assert!(!html.contains("<script"), "{}", html);
+    assert_eq!(ReportView::new(&report).unwrap().html(), html);
+
+    std::thread::spawn(|| panic!("worker <crashed>"))
+        .join()
+        .unwrap_err();
+
+    let reports: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].extension().unwrap(), "html");
+
+    let crash = std::fs::read_to_string(&reports[0]).unwrap();
+    assert!(crash.starts_with("<!DOCTYPE html>\n"), "{}", crash);
+    assert!(
+        crash.contains("<h2>Panic</h2>\n<pre>worker &lt;crashed&gt;</pre>\n"),
+        "{}",
+        crash
+    );
+    assert!(crash.contains("<summary>Backtrace</summary>"), "{}", crash);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "issue-url")]
+use nocolor_eyre::{config::HookBuilder, ErrorKind};
+use std::sync::{Arc, Mutex};
+
+struct ExitCode(i32);
+
+#[test]
+fn issue_filters_see_the_formatted_payload() {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let received = messages.clone();
+
+    let (panic_hook, _) = HookBuilder::default()
+        .panic_payload_formatter(|code: &ExitCode, f| write!(f, "exit code {}", code.0))
+        .issue_url("https://github.com/NOBLES5E/nocolor-eyre/issues/new")
+        .issue_filter(move |kind| match kind {
+            ErrorKind::NonRecoverable(_, message) => {
+                received.lock().unwrap().push(message.to_owned());
+                false
+            }
+            ErrorKind::Recoverable(_) => true,
+        })
+        .into_hooks();
+
+    let output = Arc::new(Mutex::new(String::new()));
+    let captured = output.clone();
+    std::panic::set_hook(Box::new(move |pi| {
+        *captured.lock().unwrap() = panic_hook.panic_report(pi).to_string();
+    }));
+
+    std::thread::spawn(|| std::panic::panic_any(ExitCode(3)))
+        .join()
+        .unwrap_err();
+
+    assert_eq!(*messages.lock().unwrap(), vec!["exit code 3"]);
+    let output = output.lock().unwrap();
+    assert!(!output.contains("issues/new"), "{}", output);
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "json")]
+use nocolor_eyre::{config::ReportFormat, eyre::eyre, Section};
+
+#[test]
+fn error_report_as_json() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    nocolor_eyre::config::HookBuilder::default()
+        .error_format(ReportFormat::Json)
+        .add_path_mapping(env!("CARGO_MANIFEST_DIR"), "[crate]")
+        .install()
+        .unwrap();
+
+    let report = eyre!("inner error")
+        .wrap_err("outer error")
+        .note("a note")
+        .suggestion("a suggestion");
+
+    let report = format!("{:?}", report);
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+
+    assert_eq!(report["errors"][0], "outer error");
+    assert_eq!(report["errors"][1], "inner error");
+    assert_eq!(report["sections"][0]["kind"], "note");
+    assert_eq!(report["sections"][0]["message"], "a note");
+    assert_eq!(report["sections"][1]["kind"], "suggestion");
+    assert!(!report["env"].as_array().unwrap().is_empty());
+
+    // Frame paths are rewritten like in the other formats.
+    let frames = report["backtrace"].as_array().unwrap();
+    assert!(
+        frames
+            .iter()
+            .any(|frame| frame["filename"] == "[crate]/tests/json.rs"),
+        "{}",
+        report["backtrace"]
+    );
+}
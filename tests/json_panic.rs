@@ -0,0 +1,35 @@
+#![cfg(feature = "json")]
+use nocolor_eyre::config::{HookBuilder, ReportFormat};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn panic_report_as_json() {
+    let (panic_hook, _) = HookBuilder::default()
+        .panic_format(ReportFormat::Json)
+        .panic_section("a panic section")
+        // The JSON line names the thread regardless.
+        .display_thread_section(false)
+        .into_hooks();
+
+    let output = Arc::new(Mutex::new(String::new()));
+    let captured = output.clone();
+    std::panic::set_hook(Box::new(move |pi| {
+        *captured.lock().unwrap() = panic_hook.panic_report(pi).to_string();
+    }));
+
+    std::thread::Builder::new()
+        .name("worker".into())
+        .spawn(|| panic!("boom"))
+        .unwrap()
+        .join()
+        .unwrap_err();
+
+    let output = output.lock().unwrap();
+    assert!(!output.contains('\n'));
+
+    let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(report["message"], "boom");
+    assert_eq!(report["thread"], "worker");
+    assert_eq!(report["section"], "a panic section");
+    assert_eq!(report["location"]["file"], "tests/json_panic.rs");
+}
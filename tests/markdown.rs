@@ -0,0 +1,109 @@
+mod common;
+
+use common::SharedBuffer;
+use nocolor_eyre::{
+    config::{HookBuilder, ReportFormat},
+    eyre::eyre,
+    ReportView, Section,
+};
+
+#[test]
+fn reports_render_as_markdown() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    std::env::set_var("RUST_BACKTRACE", "1");
+
+    let panics = SharedBuffer::default();
+    HookBuilder::default()
+        .error_format(ReportFormat::Markdown)
+        .panic_format(ReportFormat::Markdown)
+        .panic_section("restart the worker")
+        .add_panic_writer(panics.clone())
+        .install()
+        .unwrap();
+
+    let report = eyre!("inner error")
+        .wrap_err("outer error")
+        .note("first line\nsecond line")
+        .suggestion("try again");
+    let markdown = format!("{:?}", report);
+
+    assert!(
+        markdown.starts_with("## Error\n```\n   0: outer error\n   1: inner error\n```\n\n"),
+        "{}",
+        markdown
+    );
+    #[cfg(feature = "track-caller")]
+    assert!(
+        markdown.contains("\n## Location\n`tests/markdown.rs:"),
+        "{}",
+        markdown
+    );
+    assert!(markdown.contains("\n## Backtrace\n```\n"), "{}", markdown);
+    assert!(
+        markdown.contains(
+            "\n## Help\n- **Note:** first line\n  second line\n- **Suggestion:** try again\n"
+        ),
+        "{}",
+        markdown
+    );
+    assert!(markdown.contains("\n## Environment\n- "), "{}", markdown);
+    assert_eq!(ReportView::new(&report).unwrap().markdown(), markdown);
+
+    std::thread::spawn(|| panic!("worker crashed"))
+        .join()
+        .unwrap_err();
+    let panic = panics.contents();
+
+    assert!(
+        panic.starts_with("## Panic\n```\nworker crashed\n```\n\n"),
+        "{}",
+        panic
+    );
+    assert!(
+        panic.contains("\n## Location\n`tests/markdown.rs:"),
+        "{}",
+        panic
+    );
+    assert!(
+        panic.contains("\n## Section\n```\nrestart the worker\n```\n"),
+        "{}",
+        panic
+    );
+    assert!(panic.contains("\n## Backtrace\n```\n"), "{}", panic);
+}
+
+#[cfg(feature = "track-caller")]
+#[test]
+fn panic_reports_respect_the_location_flag() {
+    const CHILD_ENV: &str = "NOCOLOR_EYRE_MARKDOWN_CHILD";
+
+    if std::env::var_os(CHILD_ENV).is_some() {
+        HookBuilder::default()
+            .panic_format(ReportFormat::Markdown)
+            .display_location_section(false)
+            .install()
+            .unwrap();
+
+        panic!("worker crashed");
+    }
+
+    // The panic hook is installed once per process, so the check runs in a
+    // child process with its own configuration.
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "panic_reports_respect_the_location_flag",
+            "--nocapture",
+        ])
+        .env(CHILD_ENV, "1")
+        .output()
+        .unwrap();
+    let panic = String::from_utf8(output.stderr).unwrap();
+
+    assert!(
+        panic.contains("## Panic\n```\nworker crashed\n```\n"),
+        "{}",
+        panic
+    );
+    assert!(!panic.contains("## Location"), "{}", panic);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::SharedBuffer;
+use nocolor_eyre::config::HookBuilder;
+use std::sync::{Arc, Mutex};
+
+struct ExitCode(i32);
+
+struct Unregistered;
+
+#[test]
+fn custom_payloads_use_registered_formatters() {
+    let output = SharedBuffer::default();
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let received = messages.clone();
+
+    HookBuilder::default()
+        .panic_payload_formatter(|code: &ExitCode, f| write!(f, "exit code {}", code.0))
+        .add_panic_writer(output.clone())
+        .add_panic_callback(move |report| {
+            received
+                .lock()
+                .unwrap()
+                .push(report.payload_message().into_owned())
+        })
+        .install()
+        .unwrap();
+
+    std::thread::spawn(|| std::panic::panic_any(ExitCode(3)))
+        .join()
+        .unwrap_err();
+    std::thread::spawn(|| std::panic::panic_any(Unregistered))
+        .join()
+        .unwrap_err();
+
+    let output = output.contents();
+    assert!(output.contains("Message:  exit code 3"), "{}", output);
+    assert!(
+        output.contains("Message:  <non string panic payload>"),
+        "{}",
+        output
+    );
+    assert_eq!(
+        *messages.lock().unwrap(),
+        vec!["exit code 3", "<non string panic payload>"]
+    );
+}
@@ -0,0 +1,59 @@
+#![allow(deprecated)]
+use nocolor_eyre::{config::HookBuilder, section::PanicMessage};
+use std::{fmt, panic::PanicInfo, sync::OnceLock};
+
+type Hook = Box<dyn Fn(&PanicInfo<'_>) + Send + Sync + 'static>;
+
+static HOOK: OnceLock<Hook> = OnceLock::new();
+
+const CHILD_ENV: &str = "NOCOLOR_EYRE_REENTRANCY_CHILD";
+
+/// Invokes the installed panic hook again while the report is being rendered
+struct ReentrantMessage;
+
+impl PanicMessage for ReentrantMessage {
+    fn display(&self, pi: &PanicInfo<'_>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (HOOK.get().unwrap())(pi);
+        write!(f, "outer report")
+    }
+}
+
+#[test]
+fn nested_invocations_print_a_fallback_report() {
+    if std::env::var_os(CHILD_ENV).is_some() {
+        let (panic_hook, _) = HookBuilder::default()
+            .panic_message(ReentrantMessage)
+            .into_hooks();
+        let _ = HOOK.set(panic_hook.into_panic_hook());
+        std::panic::set_hook(Box::new(|pi| (HOOK.get().unwrap())(pi)));
+
+        panic!("reentrant panic");
+    }
+
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "nested_invocations_print_a_fallback_report",
+            "--nocapture",
+        ])
+        .env(CHILD_ENV, "1")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    // The test harness exits with 101 for failed tests, recursing until the
+    // stack overflows would abort instead.
+    assert_eq!(output.status.code(), Some(101), "{}", stderr);
+    assert!(
+        stderr.contains("while the panic hook was reporting another panic"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("Message:  reentrant panic"), "{}", stderr);
+    assert!(
+        stderr.contains("Location: tests/panic_reentrancy.rs:"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("outer report"), "{}", stderr);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::SharedBuffer;
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report},
+    Section,
+};
+use std::fmt;
+
+struct PanickingSection;
+
+impl fmt::Display for PanickingSection {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        panic!("section rendering failed")
+    }
+}
+
+#[inline(never)]
+fn create_report() -> Report {
+    eyre!("error")
+}
+
+#[test]
+fn rendering_degrades_gracefully() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "full");
+    std::env::set_var("COLORBT_SHOW_HIDDEN", "1");
+
+    // Point the sources of the standard library at a file that isn't valid
+    // UTF-8.
+    let library = common::std_library_dir();
+
+    let dir = std::env::temp_dir().join(format!("nocolor-eyre-non-utf8-{}", std::process::id()));
+    let source = dir.join("core/src/ops/function.rs");
+    std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+    let lines: Vec<u8> = (0..2000)
+        .flat_map(|_| b"invalid \xff utf-8\n".to_vec())
+        .collect();
+    std::fs::write(&source, lines).unwrap();
+
+    let panics = SharedBuffer::default();
+    let mut builder = HookBuilder::default().add_panic_writer(panics.clone());
+    if let Some(library) = library.as_ref() {
+        builder = builder.add_source_remap(library, &dir);
+    }
+    builder.install().unwrap();
+
+    let report = create_report()
+        .section(PanickingSection)
+        .note("rendered after the panicking section");
+    let report = format!("{:?}", report);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(
+        report.contains("<this section panicked while being rendered>"),
+        "{}",
+        report
+    );
+    assert!(
+        report.contains("Note: rendered after the panicking section"),
+        "{}",
+        report
+    );
+    // Only the placeholder shows up, the panic hook stays quiet.
+    assert_eq!(panics.contents(), "");
+    if library.is_some() {
+        assert!(report.contains("> invalid \u{fffd} utf-8"), "{}", report);
+    }
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::SharedBuffer;
+use nocolor_eyre::config::HookBuilder;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn panic_reports_are_delivered_to_every_sink() {
+    let first = SharedBuffer::default();
+    let second = SharedBuffer::default();
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let received = messages.clone();
+
+    HookBuilder::default()
+        .add_panic_writer(first.clone())
+        .add_panic_writer(second.clone())
+        .add_panic_callback(move |report| received.lock().unwrap().push(report.to_string()))
+        .install()
+        .unwrap();
+
+    std::thread::spawn(|| panic!("delivered everywhere"))
+        .join()
+        .unwrap_err();
+
+    let first = first.contents();
+    let second = second.contents();
+    let messages = messages.lock().unwrap();
+
+    assert!(first.contains("delivered everywhere"));
+    assert_eq!(first, second);
+    assert_eq!(messages.len(), 1);
+    assert_eq!(format!("{}\n", messages[0]), first);
+}
@@ -0,0 +1,34 @@
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report},
+};
+
+#[inline(never)]
+fn create_report() -> Report {
+    eyre!("error")
+}
+
+#[test]
+fn paths_are_rewritten() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    std::env::set_var("COLORBT_SHOW_HIDDEN", "1");
+
+    HookBuilder::default()
+        .workspace_root(env!("CARGO_MANIFEST_DIR"))
+        .add_path_mapping(concat!(env!("CARGO_MANIFEST_DIR"), "/tests"), "[tests]")
+        .install()
+        .unwrap();
+
+    let report = format!("{:?}", create_report());
+
+    assert!(
+        report.contains("at [tests]/path_rewriting.rs:8:5"),
+        "{}",
+        report
+    );
+    assert!(report.contains("at src/config.rs:"), "{}", report);
+    assert!(report.contains("at [eyre-0."), "{}", report);
+    assert!(report.contains("at [std]/core/src/"), "{}", report);
+    assert!(!report.contains("/registry/src/"), "{}", report);
+    assert!(!report.contains("at /rustc/"), "{}", report);
+}
@@ -0,0 +1,74 @@
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report},
+};
+
+#[inline(never)]
+fn create_report() -> Report {
+    eyre!("error")
+}
+
+#[test]
+fn raw_addresses_are_printed() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    HookBuilder::default()
+        .display_raw_addresses(true)
+        .install()
+        .unwrap();
+
+    let report = format!("{:?}", create_report());
+
+    assert!(report.contains("ip=0x"), "{}", report);
+
+    #[cfg(target_os = "linux")]
+    {
+        let exe = std::env::current_exe().unwrap();
+        assert!(
+            report.contains(&format!("module={}", exe.display())),
+            "{}",
+            report
+        );
+    }
+
+    #[cfg(all(target_os = "linux", feature = "symbolize"))]
+    symbolize(&report);
+}
+
+#[cfg(all(target_os = "linux", feature = "symbolize"))]
+fn symbolize(report: &str) {
+    // Drop the symbols from the report, as if it came from a stripped binary.
+    let stripped: String = report
+        .lines()
+        .map(|line| match line.find("raw_addresses::create_report") {
+            Some(start) => format!("{}<unknown>\n", &line[..start]),
+            None => format!("{}\n", line),
+        })
+        .collect();
+    assert!(!stripped.contains("raw_addresses::create_report"));
+
+    let dir = std::env::temp_dir().join(format!("nocolor-eyre-symbolize-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("report.txt");
+    std::fs::write(&path, stripped).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_nocolor-eyre-symbolize"))
+        .arg(std::env::current_exe().unwrap())
+        .arg(&path)
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(output.status.success());
+    let symbolized = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        symbolized.contains("raw_addresses::create_report"),
+        "{}",
+        symbolized
+    );
+    assert!(
+        symbolized.contains("tests/raw_addresses.rs:8"),
+        "{}",
+        symbolized
+    );
+}
@@ -0,0 +1,93 @@
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report},
+    ReportSection, ReportView, Section,
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("an additional error")]
+struct AdditionalError;
+
+#[inline(never)]
+fn hidden_helper() -> Report {
+    eyre!("inner error")
+}
+
+#[inline(never)]
+fn create_report() -> Report {
+    hidden_helper()
+}
+
+#[test]
+fn view_exposes_report_contents() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+
+    HookBuilder::default()
+        .hide_symbol_prefix("report_view::hidden_helper")
+        .install()
+        .unwrap();
+
+    let report = create_report()
+        .wrap_err("outer error")
+        .error(AdditionalError)
+        .note("a note")
+        .warning("a warning")
+        .suggestion("a suggestion")
+        .section("a custom section")
+        .suppress_backtrace(true);
+
+    let view = ReportView::new(&report).unwrap();
+
+    let errors: Vec<_> = view.chain().map(ToString::to_string).collect();
+    assert_eq!(errors, ["outer error", "inner error"]);
+
+    let sections: Vec<_> = view
+        .sections()
+        .map(|section| match section {
+            ReportSection::Error(error) => format!("error: {}", error),
+            ReportSection::Note(note) => format!("note: {}", note),
+            ReportSection::Warning(warning) => format!("warning: {}", warning),
+            ReportSection::Suggestion(suggestion) => format!("suggestion: {}", suggestion),
+            ReportSection::Custom(custom) => format!("custom: {}", custom),
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(
+        sections,
+        [
+            "error: an additional error",
+            "note: a note",
+            "warning: a warning",
+            "suggestion: a suggestion",
+            "custom: a custom section",
+        ]
+    );
+
+    assert!(view.backtrace_suppressed());
+    assert!(view.display_env_section());
+
+    #[cfg(feature = "track-caller")]
+    assert_eq!(view.location().unwrap().file(), "tests/report_view.rs");
+
+    // The frames are filtered by the configured rules and the default filters.
+    let names: Vec<_> = view
+        .frames()
+        .unwrap()
+        .into_iter()
+        .filter_map(|frame| frame.name)
+        .collect();
+    assert!(names
+        .iter()
+        .any(|name| name.starts_with("report_view::create_report")));
+    assert!(!names
+        .iter()
+        .any(|name| name.starts_with("report_view::hidden_helper")));
+    assert!(!names.iter().any(|name| name.starts_with("eyre::")));
+
+    // Reports of caught panics point at the panic.
+    let line = line!() + 1;
+    let report = nocolor_eyre::catch_panic(|| panic!("job failed")).unwrap_err();
+    let location = ReportView::new(&report).unwrap().location().unwrap();
+    assert_eq!(location.file(), "tests/report_view.rs");
+    assert_eq!(location.line(), line);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use nocolor_eyre::{
+    config::HookBuilder,
+    eyre::{eyre, Report},
+};
+use std::path::Path;
+
+#[inline(never)]
+fn create_report() -> Report {
+    eyre!("error")
+}
+
+fn write_source(path: &Path, marker: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let source: String = (1..=2000)
+        .map(|i| format!("{} line {}\n", marker, i))
+        .collect();
+    std::fs::write(path, source).unwrap();
+}
+
+#[test]
+fn sources_are_found_below_remaps_and_roots() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "full");
+    std::env::set_var("COLORBT_SHOW_HIDDEN", "1");
+
+    let library = match common::std_library_dir() {
+        Some(library) => library,
+        None => return,
+    };
+
+    let dir = std::env::temp_dir().join(format!("nocolor-eyre-sources-{}", std::process::id()));
+    let remapped = dir.join("remapped");
+    let rooted = dir.join("rooted");
+    write_source(&remapped.join("core/src/ops/function.rs"), "remapped");
+    write_source(
+        &rooted.join("library/core/src/panic/unwind_safe.rs"),
+        "rooted",
+    );
+    // Only suffixes with at least the parent directory are tried below a root.
+    write_source(&rooted.join("panicking.rs"), "decoy");
+
+    HookBuilder::default()
+        .add_source_remap(library, &remapped)
+        .add_source_root(&rooted)
+        .install()
+        .unwrap();
+
+    let report = format!("{:?}", create_report());
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(report.contains("> remapped line "), "{}", report);
+    assert!(report.contains("> rooted line "), "{}", report);
+    assert!(!report.contains("> decoy line "), "{}", report);
+}
@@ -0,0 +1,27 @@
+#![cfg(feature = "tracing-error")]
+use nocolor_eyre::eyre::{eyre, Report};
+use tracing::instrument;
+use tracing_error::ErrorLayer;
+use tracing_subscriber::prelude::*;
+
+#[instrument]
+fn get_error(msg: &'static str) -> Report {
+    eyre!("{}", msg)
+}
+
+#[test]
+fn spantrace_section() {
+    tracing_subscriber::registry()
+        .with(ErrorLayer::default())
+        .init();
+    nocolor_eyre::config::HookBuilder::default()
+        .add_path_mapping("tests", "[tests]")
+        .install()
+        .unwrap();
+
+    let report = format!("{:?}", get_error("test"));
+
+    assert!(report.contains("[SPANTRACE]"));
+    assert!(report.contains("spantrace::get_error with msg=\"test\""));
+    assert!(report.contains("at [tests]/spantrace.rs:"), "{}", report);
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::SharedBuffer;
+use nocolor_eyre::{config::HookBuilder, eyre::eyre};
+
+#[test]
+fn reports_name_the_thread() {
+    let panics = SharedBuffer::default();
+    HookBuilder::default()
+        .display_thread_section(true)
+        .add_panic_writer(panics.clone())
+        .install()
+        .unwrap();
+
+    let (report, id) = std::thread::Builder::new()
+        .name("worker-7".into())
+        .spawn(|| (format!("{:?}", eyre!("error")), std::thread::current().id()))
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(
+        report.contains(&format!("Thread:\n   worker-7 ({:?})", id)),
+        "{}",
+        report
+    );
+
+    let id = std::thread::Builder::new()
+        .name("worker-8".into())
+        .spawn(|| {
+            let id = std::thread::current().id();
+            std::panic::catch_unwind(|| panic!("crashed")).unwrap_err();
+            id
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    let panic = panics.contents();
+    assert!(
+        panic.contains(&format!("Thread:   worker-8 ({:?})", id)),
+        "{}",
+        panic
+    );
+}
+
+#[test]
+fn only_panic_reports_name_the_thread_by_default() {
+    const CHILD_ENV: &str = "NOCOLOR_EYRE_THREAD_SECTION_CHILD";
+
+    if std::env::var_os(CHILD_ENV).is_some() {
+        HookBuilder::default().install().unwrap();
+
+        eprintln!("{:?}", eyre!("error"));
+        panic!("crashed");
+    }
+
+    // The panic hook is installed once per process, so the check runs in a
+    // child process with its own configuration.
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "only_panic_reports_name_the_thread_by_default",
+            "--nocapture",
+        ])
+        .env(CHILD_ENV, "1")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains("Message:  crashed"), "{}", stderr);
+    assert!(
+        stderr.contains("Thread:   only_panic_reports_name_the_thread_by_default ("),
+        "{}",
+        stderr
+    );
+    assert_eq!(stderr.matches("Thread:").count(), 1, "{}", stderr);
+}